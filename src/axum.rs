@@ -0,0 +1,444 @@
+//! Serving a store with [axum](https://docs.rs/axum).
+
+use std::{error::Error as StdError, fmt, sync::Arc};
+
+use ::axum::{
+    Router,
+    extract::OriginalUri,
+    http::{Method, StatusCode, Uri, header},
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    FlatPageStore, Lookup, PageContext, PageUrl, RenderOptions, util::percent_encode_path,
+};
+
+/// Receives the request path, the response status and the error of failed
+/// requests.
+type ErrorHook = Arc<dyn Fn(&str, StatusCode, &(dyn StdError + 'static)) + Send + Sync>;
+
+/// Options for [`router_with`]
+#[derive(Clone, Default)]
+pub struct RouterOptions {
+    /// Options used to render page bodies.
+    render_options: RenderOptions,
+    /// Hook receiving the errors behind `400` and `500` responses.
+    on_error: Option<ErrorHook>,
+}
+
+impl RouterOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the options used to render page bodies, such as
+    /// [`RawHtml::Sanitize`] for pages edited by untrusted contributors.
+    ///
+    /// [`RawHtml::Sanitize`]: crate::RawHtml
+    pub fn render_options(mut self, options: RenderOptions) -> Self {
+        self.render_options = options;
+        self
+    }
+
+    /// Calls a hook with the request path, the response status and the error
+    /// behind each `400 Bad Request` and `500 Internal Server Error`, for
+    /// example to log it.
+    ///
+    /// The responses themselves only contain the status reason.
+    pub fn on_error(
+        mut self,
+        hook: impl Fn(&str, StatusCode, &(dyn StdError + 'static)) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_error = Some(Arc::new(hook));
+        self
+    }
+
+    /// Passes an error to the hook and returns the error response.
+    fn fail(&self, path: &str, status: StatusCode, error: &(dyn StdError + 'static)) -> Response {
+        if let Some(hook) = &self.on_error {
+            hook(path, status, error);
+        }
+        error_response(status)
+    }
+}
+
+impl fmt::Debug for RouterOptions {
+    /// Formats the options, omitting the error hook.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouterOptions")
+            .field("render_options", &self.render_options)
+            .field("on_error", &self.on_error.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// Creates a router serving the pages of a store.
+///
+/// The request path is looked up with [`FlatPageStore::lookup_request`], and
+/// the layout function turns the [`FlatPageStore::page_context`] into an HTML
+/// document. Missing pages get a `404 Not Found`, malformed paths a
+/// `400 Bad Request`, read or layout errors a `500 Internal Server Error`, and
+/// URLs with a wrong trailing slash a `301 Moved Permanently` to the canonical
+/// URL. Rules of the `_redirects` file respond with their own status. Methods
+/// other than `GET` and `HEAD` get a `405 Method Not Allowed`.
+///
+/// Page files are read and rendered on the blocking thread pool of the Tokio
+/// runtime.
+///
+/// The router only has a fallback, so it can be merged into or nested in
+/// another router.
+pub fn router<Extra, F, E>(store: Arc<FlatPageStore>, layout: F) -> Router
+where
+    Extra: DeserializeOwned + 'static,
+    F: Fn(&PageContext<'_, Extra>) -> Result<String, E> + Clone + Send + Sync + 'static,
+    E: Into<Box<dyn StdError + Send + Sync>>,
+{
+    router_with(store, RouterOptions::default(), layout)
+}
+
+/// Creates a router serving the pages of a store with the given options, see
+/// [`router`].
+pub fn router_with<Extra, F, E>(
+    store: Arc<FlatPageStore>,
+    options: RouterOptions,
+    layout: F,
+) -> Router
+where
+    Extra: DeserializeOwned + 'static,
+    F: Fn(&PageContext<'_, Extra>) -> Result<String, E> + Clone + Send + Sync + 'static,
+    E: Into<Box<dyn StdError + Send + Sync>>,
+{
+    Router::new().fallback(
+        move |method: Method, OriginalUri(original): OriginalUri, uri: Uri| {
+            let store = store.clone();
+            let options = options.clone();
+            let layout = layout.clone();
+            async move {
+                if method != Method::GET && method != Method::HEAD {
+                    return method_not_allowed();
+                }
+                let path = uri.path();
+                match store.lookup_request(path) {
+                    Ok(Lookup::Found { url, .. }) => {
+                        let (store, url, render_options) =
+                            (store.clone(), url.clone(), options.clone());
+                        let rendered = tokio::task::spawn_blocking(move || {
+                            render(&store, &render_options, url.as_str(), &layout)
+                        })
+                        .await;
+                        match rendered {
+                            Ok(response) => response,
+                            Err(error) => {
+                                options.fail(path, StatusCode::INTERNAL_SERVER_ERROR, &error)
+                            }
+                        }
+                    }
+                    Ok(Lookup::Redirect(url)) => redirect(&original, &uri, &url),
+                    Ok(Lookup::Rule { location, status }) => {
+                        rule(&original, &uri, &location, status)
+                    }
+                    Ok(_) => error_response(StatusCode::NOT_FOUND),
+                    Err(error) => options.fail(path, StatusCode::BAD_REQUEST, &error),
+                }
+            }
+        },
+    )
+}
+
+/// Returns a permanent redirect to a canonical page URL, keeping the prefix
+/// of nested routers and the query string.
+fn redirect(original: &Uri, uri: &Uri, url: &PageUrl) -> Response {
+    let prefix = original.path().strip_suffix(uri.path()).unwrap_or_default();
+    let location = match uri.query() {
+        Some(query) => format!("{prefix}{}?{query}", percent_encode_path(url.as_str())),
+        None => format!("{prefix}{}", percent_encode_path(url.as_str())),
+    };
+    (
+        StatusCode::MOVED_PERMANENTLY,
+        [(header::LOCATION, location)],
+    )
+        .into_response()
+}
+
+/// Returns the redirect of a `_redirects` rule.
+///
+/// Paths on the same site are percent-encoded and keep the prefix of nested
+/// routers, and the query string unless the destination has its own.
+fn rule(original: &Uri, uri: &Uri, location: &str, status: u16) -> Response {
+    let Ok(status) = StatusCode::from_u16(status) else {
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR);
+    };
+    let location = if location.starts_with('/') && !location.starts_with("//") {
+        let prefix = original.path().strip_suffix(uri.path()).unwrap_or_default();
+        let (path, fragment) = location
+            .split_once('#')
+            .map_or((location, None), |(path, fragment)| (path, Some(fragment)));
+        let (path, query) = path
+            .split_once('?')
+            .map_or((path, uri.query()), |(path, query)| (path, Some(query)));
+        let mut location = format!("{prefix}{}", percent_encode_path(path));
+        if let Some(query) = query {
+            location = format!("{location}?{query}");
+        }
+        if let Some(fragment) = fragment {
+            location = format!("{location}#{fragment}");
+        }
+        location
+    } else {
+        location.to_string()
+    };
+    (status, [(header::LOCATION, location)]).into_response()
+}
+
+/// Renders a page response.
+fn render<Extra, F, E>(
+    store: &FlatPageStore,
+    options: &RouterOptions,
+    path: &str,
+    layout: &F,
+) -> Response
+where
+    Extra: DeserializeOwned,
+    F: Fn(&PageContext<'_, Extra>) -> Result<String, E>,
+    E: Into<Box<dyn StdError + Send + Sync>>,
+{
+    let context = match store.page_context_with::<Extra>(path, &options.render_options) {
+        Ok(Some(context)) => context,
+        Ok(None) => return error_response(StatusCode::NOT_FOUND),
+        Err(error) => return options.fail(path, StatusCode::INTERNAL_SERVER_ERROR, &error),
+    };
+    match layout(&context) {
+        Ok(html) => ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], html).into_response(),
+        Err(error) => options.fail(
+            path,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            error.into().as_ref(),
+        ),
+    }
+}
+
+/// Returns a `405 Method Not Allowed` listing the served methods.
+fn method_not_allowed() -> Response {
+    let mut response = error_response(StatusCode::METHOD_NOT_ALLOWED);
+    response
+        .headers_mut()
+        .insert(header::ALLOW, header::HeaderValue::from_static("GET, HEAD"));
+    response
+}
+
+/// Returns a plain-text response with the status reason as the body.
+fn error_response(status: StatusCode) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        status.canonical_reason().unwrap_or_default(),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::Infallible, sync::Mutex};
+
+    use ::axum::{
+        body::{Body, to_bytes},
+        http::Request,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{
+        RawHtml, SegmentPolicy, StoreOptions,
+        test_helpers::{TestDir, write_page},
+    };
+
+    fn layout(context: &PageContext<'_>) -> Result<String, Infallible> {
+        Ok(format!(
+            "<title>{}</title>{}",
+            context.meta.title, context.content
+        ))
+    }
+
+    async fn get(router: Router, path: &str) -> (StatusCode, String, String) {
+        let response = router
+            .oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let content_type = response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            content_type,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_router() {
+        let root = TestDir::new();
+        write_page(root.path(), "guides/install.md", "# Install");
+        std::fs::write(
+            root.path().join("_redirects"),
+            "/old/* /guides/:splat 302\n/ext https://example.com/\n/setup /guides/über?os=linux#apt\n",
+        )
+        .unwrap();
+        let options = StoreOptions::new().segment_policy(SegmentPolicy::Unicode);
+        let store = Arc::new(FlatPageStore::read_dir_with(root.path(), &options).unwrap());
+        let router = router(store.clone(), layout);
+
+        assert_eq!(
+            get(router.clone(), "/guides/install?x=1").await,
+            (
+                StatusCode::OK,
+                "text/html; charset=utf-8".into(),
+                "<title>Install</title><h1>Install</h1>\n".into()
+            )
+        );
+        assert_eq!(
+            get(router.clone(), "/missing").await,
+            (
+                StatusCode::NOT_FOUND,
+                "text/plain; charset=utf-8".into(),
+                "Not Found".into()
+            )
+        );
+
+        assert_eq!(
+            get(router.clone(), "/guides/inst%61ll").await.0,
+            StatusCode::OK
+        );
+        assert_eq!(
+            get(router.clone(), "/guides/%2e%2e/secret").await,
+            (
+                StatusCode::BAD_REQUEST,
+                "text/plain; charset=utf-8".into(),
+                "Bad Request".into()
+            )
+        );
+
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let options = RouterOptions::new().on_error({
+            let errors = errors.clone();
+            move |path, status, error| {
+                errors
+                    .lock()
+                    .unwrap()
+                    .push(format!("{path} {} {error}", status.as_u16()));
+            }
+        });
+        let failing = router_with(store.clone(), options, |_: &PageContext<'_>| Err("broken"));
+        assert_eq!(
+            get(failing.clone(), "/guides/install").await.0,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            get(failing, "/guides/%2e%2e/secret").await.0,
+            StatusCode::BAD_REQUEST
+        );
+        {
+            let errors = errors.lock().unwrap();
+            assert_eq!(errors[0], "/guides/install 500 broken");
+            assert!(errors[1].starts_with("/guides/%2e%2e/secret 400 "));
+        }
+
+        for method in [Method::POST, Method::DELETE] {
+            let response = router
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(method)
+                        .uri("/guides/install")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+            assert_eq!(response.headers()[header::ALLOW], "GET, HEAD");
+        }
+        let response = router
+            .clone()
+            .oneshot(
+                Request::head("/guides/install")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = Router::new()
+            .nest("/docs", router.clone())
+            .oneshot(
+                Request::get("/docs/guides/install/?x=1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "/docs/guides/install?x=1"
+        );
+
+        for (path, status, location) in [
+            (
+                "/docs/old/install",
+                StatusCode::FOUND,
+                "/docs/guides/install",
+            ),
+            (
+                "/docs/old/install?x=1",
+                StatusCode::FOUND,
+                "/docs/guides/install?x=1",
+            ),
+            (
+                "/docs/old/%C3%BCber",
+                StatusCode::FOUND,
+                "/docs/guides/%C3%BCber",
+            ),
+            (
+                "/docs/setup?x=1",
+                StatusCode::MOVED_PERMANENTLY,
+                "/docs/guides/%C3%BCber?os=linux#apt",
+            ),
+            (
+                "/docs/ext?x=1",
+                StatusCode::MOVED_PERMANENTLY,
+                "https://example.com/",
+            ),
+        ] {
+            let response = Router::new()
+                .nest("/docs", router.clone())
+                .oneshot(Request::get(path).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+            assert_eq!(response.headers()[header::LOCATION], location);
+        }
+
+        std::fs::write(root.path().join("guides/install.md"), "---\ntitle: [\n---").unwrap();
+        assert_eq!(
+            get(router, "/guides/install").await.0,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_router_with_render_options() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "<script>alert(1)</script>");
+        let store = Arc::new(FlatPageStore::read_dir(root.path()).unwrap());
+        let options =
+            RouterOptions::new().render_options(RenderOptions::new().raw_html(RawHtml::Escape));
+        let (_, _, body) = get(router_with(store, options, layout), "/").await;
+        assert!(body.contains("&lt;script&gt;"), "{body}");
+    }
+}
@@ -0,0 +1,123 @@
+//! HTTP cache validators and conditional request evaluation.
+
+use std::time::SystemTime;
+
+use crate::util::Timestamp;
+
+/// FNV-1a 64-bit offset basis.
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+/// FNV-1a 64-bit prime.
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// HTTP cache validators of a page file
+///
+/// [`CacheValidators::etag`] and [`CacheValidators::last_modified`] give the
+/// `ETag` and `Last-Modified` response headers, and
+/// [`CacheValidators::is_not_modified`] evaluates the conditional request
+/// headers to decide whether a `304 Not Modified` can be returned.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CacheValidators {
+    /// FNV-1a hash of the file content
+    pub content_hash: u64,
+    /// Modification time of the file, if the page was read from a file and
+    /// the platform supports it
+    pub modified: Option<SystemTime>,
+}
+
+impl CacheValidators {
+    /// Computes the validators of a page content.
+    pub(crate) fn new(content: &str, modified: Option<SystemTime>) -> Self {
+        let content_hash = content.bytes().fold(FNV_OFFSET, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        });
+        Self {
+            content_hash,
+            modified,
+        }
+    }
+
+    /// Returns the strong `ETag` header value, such as
+    /// `"af63dc4c8601ec8c"` (quotes included).
+    pub fn etag(&self) -> String {
+        format!("\"{:016x}\"", self.content_hash)
+    }
+
+    /// Returns the `Last-Modified` header value, such as
+    /// `Sun, 06 Nov 1994 08:49:37 GMT`.
+    pub fn last_modified(&self) -> Option<String> {
+        self.modified
+            .map(|modified| Timestamp::from(modified).to_http_date())
+    }
+
+    /// Returns whether the client's cached copy is still fresh, given the
+    /// `If-None-Match` and `If-Modified-Since` request headers.
+    ///
+    /// As HTTP requires, `If-Modified-Since` is ignored when `If-None-Match`
+    /// is present. Entity tags are compared weakly, so `W/"…"` matches too.
+    /// Unparsable dates never match.
+    pub fn is_not_modified(
+        &self,
+        if_none_match: Option<&str>,
+        if_modified_since: Option<&str>,
+    ) -> bool {
+        if let Some(if_none_match) = if_none_match {
+            let etag = self.etag();
+            return if_none_match
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag);
+        }
+        let (Some(modified), Some(since)) = (
+            self.modified,
+            if_modified_since.and_then(Timestamp::parse_http_date),
+        ) else {
+            return false;
+        };
+        Timestamp::from(modified) <= since
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    #[test]
+    fn test_validators() {
+        let modified = UNIX_EPOCH + Duration::from_millis(784_111_777_500);
+        let validators = CacheValidators::new("a", Some(modified));
+        assert_eq!(validators.etag(), "\"af63dc4c8601ec8c\"");
+        assert_eq!(
+            validators.last_modified().as_deref(),
+            Some("Sun, 06 Nov 1994 08:49:37 GMT")
+        );
+        assert_eq!(CacheValidators::new("", None).last_modified(), None);
+    }
+
+    #[test]
+    fn test_is_not_modified() {
+        let modified = UNIX_EPOCH + Duration::from_millis(784_111_777_500);
+        let validators = CacheValidators::new("a", Some(modified));
+        let etag = validators.etag();
+        let check = |none_match: Option<&str>, modified_since: Option<&str>| {
+            validators.is_not_modified(none_match, modified_since)
+        };
+
+        assert!(check(Some(&etag), None));
+        assert!(check(Some(&format!("\"x\", W/{etag}")), None));
+        assert!(check(Some("*"), None));
+        assert!(!check(Some("\"x\""), None));
+        assert!(!check(Some("\"x\""), Some("Sun, 06 Nov 1994 08:49:37 GMT")));
+
+        assert!(check(None, Some("Sun, 06 Nov 1994 08:49:37 GMT")));
+        assert!(check(None, Some("Mon, 07 Nov 1994 00:00:00 GMT")));
+        assert!(!check(None, Some("Sun, 06 Nov 1994 08:49:36 GMT")));
+        assert!(!check(None, Some("yesterday")));
+        assert!(!check(None, None));
+        assert!(
+            !CacheValidators::new("a", None)
+                .is_not_modified(None, Some("Sun, 06 Nov 1994 08:49:37 GMT"))
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Compact search index export for client-side search widgets.
+
+use std::{collections::BTreeMap, path::Path};
+
+use serde::Serialize;
+
+use crate::{FlatPage, FlatPageMeta, PageUrl, Result, Tokenizer};
+
+/// Search index for client-side search, see
+/// [`FlatPageStore::client_search_index`]
+///
+/// Serialize it, e.g. with `serde_json`, and load it in the browser.
+///
+/// [`FlatPageStore::client_search_index`]: crate::FlatPageStore::client_search_index
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ClientSearchIndex {
+    /// Indexed pages sorted by URL
+    pub pages: Vec<ClientSearchPage>,
+    /// Maps body terms to `[page index, [word positions]]` pairs
+    pub terms: BTreeMap<String, Vec<(usize, Vec<usize>)>>,
+}
+
+/// A page of [`ClientSearchIndex`]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ClientSearchPage {
+    /// Page URL
+    pub url: String,
+    /// Plain-text page title
+    pub title: String,
+    /// Page description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ClientSearchIndex {
+    /// Reads page bodies from their files and builds the index, keeping the
+    /// order of the pages.
+    pub(crate) fn build(
+        pages: Vec<(&PageUrl, &FlatPageMeta, &Path)>,
+        tokenizer: &Tokenizer,
+    ) -> Result<Self> {
+        let mut index = Self {
+            pages: Vec::with_capacity(pages.len()),
+            terms: BTreeMap::new(),
+        };
+        for (url, meta, path) in pages {
+            let Some(page) = FlatPage::<()>::by_path(path)? else {
+                continue;
+            };
+            let page_index = index.pages.len();
+            let mut positions: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+            for (position, term) in tokenizer.tokenize(&page.plain_text()) {
+                positions.entry(term).or_default().push(position);
+            }
+            for (term, positions) in positions {
+                index
+                    .terms
+                    .entry(term)
+                    .or_default()
+                    .push((page_index, positions));
+            }
+            index.pages.push(ClientSearchPage {
+                url: url.to_string(),
+                title: meta.title_text(),
+                description: meta.description.clone(),
+            });
+        }
+        Ok(index)
+    }
+}
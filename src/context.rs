@@ -0,0 +1,132 @@
+//! Serializable page data for template engines.
+
+use serde::Serialize;
+
+use crate::{FlatPage, FlatPageMeta};
+
+/// A link to a page, see [`FlatPageStore::breadcrumbs`] and
+/// [`FlatPageStore::children`]
+///
+/// [`FlatPageStore::breadcrumbs`]: crate::FlatPageStore::breadcrumbs
+/// [`FlatPageStore::children`]: crate::FlatPageStore::children
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct NavLink {
+    /// URL of the page
+    pub url: String,
+    /// Plain-text title of the page
+    pub title: String,
+}
+
+/// A heading of a page, see [`FlatPage::headings`]
+///
+/// A list of headings serves as a table of contents: indent entries by
+/// `level` and link to `#{id}` when the heading has an explicit id, such as
+/// `## Install {#install}` with [`RenderOptions::heading_attributes`].
+///
+/// [`RenderOptions::heading_attributes`]: crate::RenderOptions::heading_attributes
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Heading {
+    /// Heading level, from 1 for `#` to 6 for `######`
+    pub level: u8,
+    /// Explicit heading id, `None` for headings without one
+    pub id: Option<String>,
+    /// Plain-text heading content
+    pub text: String,
+}
+
+/// Everything a template needs to render a page, see
+/// [`FlatPageStore::page_context`]
+///
+/// Serializes into a map with `url`, `page`, `meta`, `content`, `toc`,
+/// `breadcrumbs` and `children` keys. `content` is the rendered HTML body, so
+/// it has to be marked as safe in templates escaping HTML.
+///
+/// [`FlatPageStore::page_context`]: crate::FlatPageStore::page_context
+#[derive(Debug, Serialize)]
+pub struct PageContext<'a, Extra = ()> {
+    /// URL of the page
+    pub url: String,
+    /// The page itself
+    pub page: FlatPage<Extra>,
+    /// Cached metadata of the page
+    pub meta: &'a FlatPageMeta,
+    /// Rendered page body
+    pub content: String,
+    /// Headings of the page body, see [`Heading`]
+    pub toc: Vec<Heading>,
+    /// Links to the folder index pages above the page, from the root, and to
+    /// the page itself
+    pub breadcrumbs: Vec<NavLink>,
+    /// Links to pages directly inside a folder index page
+    pub children: Vec<NavLink>,
+}
+
+#[cfg(feature = "minijinja")]
+impl<Extra: Serialize> PageContext<'_, Extra> {
+    /// Renders a [minijinja](https://docs.rs/minijinja) template with the
+    /// context.
+    #[cfg_attr(docsrs, doc(cfg(feature = "minijinja")))]
+    pub fn render_minijinja(
+        &self,
+        template: &minijinja::Template<'_, '_>,
+    ) -> Result<String, minijinja::Error> {
+        template.render(self)
+    }
+}
+
+#[cfg(feature = "tera")]
+impl<Extra: Serialize> PageContext<'_, Extra> {
+    /// Renders a [Tera](https://docs.rs/tera) template with the context.
+    #[cfg_attr(docsrs, doc(cfg(feature = "tera")))]
+    pub fn render_tera(&self, tera: &tera::Tera, template_name: &str) -> tera::Result<String> {
+        tera.render(template_name, &tera::Context::from_serialize(self)?)
+    }
+}
+
+#[cfg(all(test, any(feature = "minijinja", feature = "tera")))]
+mod tests {
+    use crate::{
+        FlatPageStore,
+        test_helpers::{TestDir, write_page},
+    };
+
+    /// Template source working in both engines.
+    const TEMPLATE: &str = "{% for link in breadcrumbs %}{{ link.title }} / {% endfor %}\
+        {{ meta.title }} ({{ toc | length }}): {{ content | safe }}";
+
+    fn store() -> (TestDir, FlatPageStore) {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# Home");
+        write_page(root.path(), "about.md", "# About\n\nHi & bye");
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        (root, store)
+    }
+
+    #[cfg(feature = "minijinja")]
+    #[test]
+    fn test_render_minijinja() {
+        let (_root, store) = store();
+        let mut env = minijinja::Environment::new();
+        env.add_template("page.html", TEMPLATE).unwrap();
+        let context = store.page_context::<()>("/about").unwrap().unwrap();
+        assert_eq!(
+            context
+                .render_minijinja(&env.get_template("page.html").unwrap())
+                .unwrap(),
+            "Home / About / About (1): <h1>About</h1>\n<p>Hi &amp; bye</p>\n"
+        );
+    }
+
+    #[cfg(feature = "tera")]
+    #[test]
+    fn test_render_tera() {
+        let (_root, store) = store();
+        let mut tera = tera::Tera::default();
+        tera.add_raw_template("page.html", TEMPLATE).unwrap();
+        let context = store.page_context::<()>("/about").unwrap().unwrap();
+        assert_eq!(
+            context.render_tera(&tera, "page.html").unwrap(),
+            "Home / About / About (1): <h1>About</h1>\n<p>Hi &amp; bye</p>\n"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-//! Error types for page loading and directory scanning.
+//! Error types for page loading, directory scanning and exporting.
 
 use std::{io, path::PathBuf};
 
@@ -24,6 +24,22 @@ pub enum Error {
         /// The path to the directory being read
         path: PathBuf,
     },
+    /// A string is not a valid page URL.
+    #[error("invalid page URL {url:?}: {problem}")]
+    InvalidUrl {
+        /// The rejected URL
+        url: String,
+        /// Why the URL was rejected
+        problem: UrlProblem,
+    },
+    /// A file path doesn't belong to a page.
+    #[error("not a page path {path:?}: {problem}")]
+    InvalidPath {
+        /// The rejected path
+        path: PathBuf,
+        /// Why the path was rejected
+        problem: PathProblem,
+    },
     /// Failed to read path metadata.
     #[error("failed to read filesystem metadata: {path}")]
     ReadMetadata {
@@ -42,6 +58,94 @@ pub enum Error {
         /// The path to the file
         path: PathBuf,
     },
+    /// Failed to create a directory.
+    #[error("failed to create directory: {path}")]
+    CreateDir {
+        /// The underlying I/O error
+        #[source]
+        source: io::Error,
+        /// The path to the directory being created
+        path: PathBuf,
+    },
+    /// Failed to write a file.
+    #[error("failed to write file: {path}")]
+    WriteFile {
+        /// The underlying I/O error
+        #[source]
+        source: io::Error,
+        /// The path to the file
+        path: PathBuf,
+    },
+    /// Failed to copy a file.
+    #[error("failed to copy file: {from} -> {to}")]
+    CopyFile {
+        /// The underlying I/O error
+        #[source]
+        source: io::Error,
+        /// The path to the source file
+        from: PathBuf,
+        /// The path to the destination file
+        to: PathBuf,
+    },
+    /// A template callback failed to render a page.
+    #[error("failed to render page: {url}")]
+    Template {
+        /// The error returned by the template
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+        /// The URL of the page
+        url: String,
+    },
+    /// Two pages, or a page and an asset, would be exported to the same file.
+    #[error("{first} and {second} are both exported to {path}")]
+    OutputConflict {
+        /// The output file
+        path: PathBuf,
+        /// The URL of the first page
+        first: String,
+        /// The URL of the second page, or the path of the asset relative to
+        /// the store root
+        second: String,
+    },
+    /// A redirect in page frontmatter has an invalid URL.
+    #[error("invalid redirect URL {url:?} in {path}: {problem}")]
+    InvalidRedirect {
+        /// The page file declaring the redirect
+        path: PathBuf,
+        /// The rejected URL
+        url: String,
+        /// Why the URL was rejected
+        problem: UrlProblem,
+    },
+    /// A redirect source is also a page or the source of another redirect.
+    #[error("redirect from {url} in {second} conflicts with {first}")]
+    RedirectConflict {
+        /// The redirect source URL
+        url: String,
+        /// The page file, or the file declaring the other redirect
+        first: PathBuf,
+        /// The file declaring the conflicting redirect
+        second: PathBuf,
+    },
+    /// A rule of the `_redirects` file is invalid.
+    #[error("invalid redirect rule at {path}:{line}: {problem}")]
+    InvalidRedirectRule {
+        /// The `_redirects` file
+        path: PathBuf,
+        /// 1-based line number of the rule
+        line: usize,
+        /// Why the rule was rejected
+        problem: RuleProblem,
+    },
+    /// Two page files differ only in letter case, which is ambiguous for
+    /// case-insensitive lookups.
+    #[error("page files {first} and {second} differ only in case")]
+    CaseConflict {
+        /// The page file read first
+        first: PathBuf,
+        /// The conflicting page file
+        second: PathBuf,
+    },
 }
 
 impl Error {
@@ -64,6 +168,22 @@ impl Error {
         }
     }
 
+    /// Creates an invalid page URL error.
+    pub fn invalid_url(url: impl Into<String>, problem: UrlProblem) -> Self {
+        Self::InvalidUrl {
+            url: url.into(),
+            problem,
+        }
+    }
+
+    /// Creates an invalid page path error.
+    pub fn invalid_path(path: impl Into<PathBuf>, problem: PathProblem) -> Self {
+        Self::InvalidPath {
+            path: path.into(),
+            problem,
+        }
+    }
+
     /// Creates a filesystem metadata error for the given path.
     pub fn read_metadata(source: io::Error, path: impl Into<PathBuf>) -> Self {
         Self::ReadMetadata {
@@ -79,6 +199,116 @@ impl Error {
             path: path.into(),
         }
     }
+
+    /// Creates a directory-creation error for the given path.
+    pub fn create_dir(source: io::Error, path: impl Into<PathBuf>) -> Self {
+        Self::CreateDir {
+            source,
+            path: path.into(),
+        }
+    }
+
+    /// Creates a file-writing error for the given path.
+    pub fn write_file(source: io::Error, path: impl Into<PathBuf>) -> Self {
+        Self::WriteFile {
+            source,
+            path: path.into(),
+        }
+    }
+
+    /// Creates a file-copying error for the given paths.
+    pub fn copy_file(source: io::Error, from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        Self::CopyFile {
+            source,
+            from: from.into(),
+            to: to.into(),
+        }
+    }
+
+    /// Creates a template error for the given page URL.
+    pub fn template(
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+        url: impl Into<String>,
+    ) -> Self {
+        Self::Template {
+            source: source.into(),
+            url: url.into(),
+        }
+    }
+}
+
+/// Reason a string is not a valid page URL, see [`Error::InvalidUrl`]
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum UrlProblem {
+    /// The URL is empty.
+    #[error("the URL is empty")]
+    Empty,
+    /// The URL doesn't start with a slash.
+    #[error("the URL doesn't start with `/`")]
+    MissingLeadingSlash,
+    /// A folder URL, such as a feed prefix, doesn't end with a slash.
+    #[error("the URL doesn't end with `/`")]
+    MissingTrailingSlash,
+    /// The URL contains an empty segment, as in `/foo//bar`.
+    #[error("the URL contains an empty segment")]
+    EmptySegment,
+    /// A segment contains disallowed characters or is `.` or `..`.
+    #[error("invalid segment {0:?}")]
+    InvalidSegment(String),
+    /// A `%` isn't followed by two hex digits, or the decoded bytes aren't
+    /// valid UTF-8.
+    #[error("invalid percent-encoding")]
+    InvalidPercentEncoding,
+    /// The page mapping has no file name for the URL.
+    #[error("no page file maps to the URL")]
+    Unmapped,
+}
+
+/// Reason a rule of the `_redirects` file is invalid, see
+/// [`Error::InvalidRedirectRule`]
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum RuleProblem {
+    /// The rule has a source but no destination.
+    #[error("the rule has no destination")]
+    MissingDestination,
+    /// The source isn't a page URL or a `/folder/*` pattern.
+    #[error("invalid source: {0}")]
+    InvalidSource(UrlProblem),
+    /// The destination is neither a page URL nor an external URL.
+    #[error("invalid destination: {0}")]
+    InvalidDestination(UrlProblem),
+    /// The status isn't 301, 302, 303, 307 or 308.
+    #[error("unsupported status {0:?}")]
+    UnsupportedStatus(String),
+    /// There are more fields after the status.
+    #[error("unexpected field after the status")]
+    UnexpectedField,
+}
+
+/// Reason a file path doesn't belong to a page, see [`Error::InvalidPath`]
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum PathProblem {
+    /// The path is empty.
+    #[error("the path is empty")]
+    Empty,
+    /// An absolute path is outside the pages root.
+    #[error("the path is outside the pages root")]
+    OutsideRoot,
+    /// The path contains `.`, `..` or a root component.
+    #[error("the path isn't a plain relative path")]
+    NotNormalized,
+    /// The path isn't valid UTF-8.
+    #[error("the path isn't valid UTF-8")]
+    NotUtf8,
+    /// The file doesn't have the `.md` extension.
+    #[error("not a Markdown file")]
+    NotMarkdown,
+    /// A folder or file name contains disallowed characters.
+    #[error("invalid segment {0:?}")]
+    InvalidSegment(String),
 }
 
 /// The crate's result type
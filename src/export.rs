@@ -0,0 +1,354 @@
+//! Static HTML export of a store.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+use crate::{Error, FlatPage, FlatPageStore, PageMapping, RenderOptions, Result};
+
+/// Output file naming of non-index pages
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ExportLayout {
+    /// `/guides/install` is written to `guides/install.html`
+    #[default]
+    HtmlFiles,
+    /// `/guides/install` is written to `guides/install/index.html`, so it's
+    /// served without the extension by most static hosts
+    Directories,
+}
+
+/// A page passed to the template callback of [`StaticExport::write`]
+#[derive(Debug)]
+pub struct ExportedPage<'a, Extra = ()> {
+    /// URL of the page
+    pub url: &'a str,
+    /// The page itself
+    pub page: &'a FlatPage<Extra>,
+    /// Rendered page body
+    pub content: String,
+}
+
+/// Static site exporter
+///
+/// Renders every page of a store into an HTML file and copies other files of
+/// the store directory, such as images, alongside. Folder index pages, such
+/// as `/guides/`, are written to `guides/index.html`; other pages follow the
+/// [`ExportLayout`].
+#[derive(Clone, Debug, Default)]
+pub struct StaticExport {
+    /// Output file naming.
+    layout: ExportLayout,
+    /// Markdown rendering options.
+    render_options: RenderOptions,
+    /// Whether to copy files other than pages.
+    copy_assets: bool,
+}
+
+impl StaticExport {
+    /// Creates an exporter with the default layout that copies assets.
+    pub fn new() -> Self {
+        Self {
+            copy_assets: true,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the output file naming, [`ExportLayout::HtmlFiles`] by default.
+    pub fn layout(mut self, layout: ExportLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets the options used to render page bodies.
+    pub fn render_options(mut self, options: RenderOptions) -> Self {
+        self.render_options = options;
+        self
+    }
+
+    /// Turns copying of files other than pages on or off, on by default.
+    ///
+    /// Files with a page extension of the store's [`PageMapping`] are never
+    /// copied, even if they aren't exported as pages.
+    pub fn copy_assets(mut self, enabled: bool) -> Self {
+        self.copy_assets = enabled;
+        self
+    }
+
+    /// Returns the output file of a URL, relative to the output directory.
+    pub fn output_path(&self, url: &str) -> PathBuf {
+        let path = url.trim_start_matches('/');
+        if path.is_empty() || path.ends_with('/') {
+            return PathBuf::from(format!("{path}index.html"));
+        }
+        match self.layout {
+            ExportLayout::HtmlFiles => PathBuf::from(format!("{path}.html")),
+            ExportLayout::Directories => Path::new(path).join("index.html"),
+        }
+    }
+
+    /// Renders every page of the store into the output directory.
+    ///
+    /// The template callback wraps the rendered body into a full HTML
+    /// document. Its errors are returned as [`Error::Template`].
+    pub fn write<Extra, F, E>(
+        &self,
+        store: &FlatPageStore,
+        out_dir: impl AsRef<Path>,
+        mut template: F,
+    ) -> Result<()>
+    where
+        Extra: DeserializeOwned,
+        F: FnMut(&ExportedPage<'_, Extra>) -> std::result::Result<String, E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let out_dir = out_dir.as_ref();
+        let mut outputs: HashMap<PathBuf, &str> = HashMap::new();
+        for (url, _, path) in store.page_files() {
+            let url = url.as_ref();
+            let output = self.output_path(url);
+            if let Some(first) = outputs.insert(output.clone(), url) {
+                return Err(Error::OutputConflict {
+                    path: out_dir.join(output),
+                    first: first.to_string(),
+                    second: url.to_string(),
+                });
+            }
+
+            let Some(page) = FlatPage::<Extra>::by_path(path)? else {
+                continue;
+            };
+            let exported = ExportedPage {
+                url,
+                content: page.html_with(&self.render_options),
+                page: &page,
+            };
+            let html = template(&exported).map_err(|e| Error::template(e, url))?;
+            write_file(&out_dir.join(output), html.as_bytes())?;
+        }
+
+        if self.copy_assets {
+            fs::create_dir_all(out_dir).map_err(|e| Error::create_dir(e, out_dir))?;
+            let assets = Assets {
+                root: store.root(),
+                mapping: store.mapping(),
+                out_dir,
+                canonical_out_dir: fs::canonicalize(out_dir)
+                    .map_err(|e| Error::read_metadata(e, out_dir))?,
+                outputs: &outputs,
+            };
+            assets.copy(store.root())?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a file, creating its parent directories.
+fn write_file(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| Error::create_dir(e, parent))?;
+    }
+    fs::write(path, content).map_err(|e| Error::write_file(e, path))
+}
+
+/// Files other than pages to copy into the output directory.
+struct Assets<'a> {
+    /// The store directory.
+    root: &'a Path,
+    /// Mapping whose page files are skipped.
+    mapping: &'a PageMapping,
+    /// The output directory as given.
+    out_dir: &'a Path,
+    /// The output directory with symlinks and relative parts resolved, so it
+    /// can be skipped however it's spelled.
+    canonical_out_dir: PathBuf,
+    /// Page URLs by their output paths relative to the output directory.
+    outputs: &'a HashMap<PathBuf, &'a str>,
+}
+
+impl Assets<'_> {
+    /// Recursively copies the files of a directory, skipping hidden entries
+    /// and the output directory.
+    fn copy(&self, dir: &Path) -> Result<()> {
+        for entry in fs::read_dir(dir).map_err(|e| Error::read_dir(e, dir))? {
+            let entry = entry.map_err(|e| Error::read_dir(e, dir))?;
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let file_type = entry.file_type().map_err(|e| Error::read_dir(e, dir))?;
+            if file_type.is_dir() {
+                if fs::canonicalize(&path).is_ok_and(|path| path == self.canonical_out_dir) {
+                    continue;
+                }
+                self.copy(&path)?;
+                continue;
+            }
+            let is_page = self
+                .mapping
+                .split_extension(&entry.file_name().to_string_lossy())
+                .is_some();
+            if !file_type.is_file() || is_page {
+                continue;
+            }
+            let Ok(relative_path) = path.strip_prefix(self.root) else {
+                continue;
+            };
+            let target = self.out_dir.join(relative_path);
+            if let Some(url) = self.outputs.get(relative_path) {
+                return Err(Error::OutputConflict {
+                    path: target,
+                    first: url.to_string(),
+                    second: relative_path.display().to_string(),
+                });
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| Error::create_dir(e, parent))?;
+            }
+            fs::copy(&path, &target).map_err(|e| Error::copy_file(e, &path, &target))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+    use crate::{
+        StoreOptions,
+        test_helpers::{TestDir, write_page},
+    };
+
+    fn layout(page: &ExportedPage<'_>) -> std::result::Result<String, Infallible> {
+        Ok(format!(
+            "<title>{}</title>\n{}",
+            page.page.title_text(),
+            page.content
+        ))
+    }
+
+    #[test]
+    fn export_output_paths() {
+        let export = StaticExport::new();
+        assert_eq!(export.output_path("/"), Path::new("index.html"));
+        assert_eq!(
+            export.output_path("/guides/"),
+            Path::new("guides/index.html")
+        );
+        assert_eq!(
+            export.output_path("/guides/install"),
+            Path::new("guides/install.html")
+        );
+
+        let export = export.layout(ExportLayout::Directories);
+        assert_eq!(
+            export.output_path("/guides/"),
+            Path::new("guides/index.html")
+        );
+        assert_eq!(
+            export.output_path("/guides/install"),
+            Path::new("guides/install/index.html")
+        );
+    }
+
+    #[test]
+    fn export_writes_pages_and_assets() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# Home");
+        write_page(
+            root.path(),
+            "guides/install.md",
+            "# Install\n\n![](logo.png)",
+        );
+        write_page(root.path(), "guides/logo.png", "png");
+        write_page(root.path(), ".git/config", "");
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+
+        let out = TestDir::new();
+        StaticExport::new()
+            .write(&store, out.path(), layout)
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(out.path().join("index.html")).unwrap(),
+            "<title>Home</title>\n<h1>Home</h1>\n"
+        );
+        assert!(
+            fs::read_to_string(out.path().join("guides/install.html"))
+                .unwrap()
+                .starts_with("<title>Install</title>\n")
+        );
+        assert_eq!(
+            fs::read_to_string(out.path().join("guides/logo.png")).unwrap(),
+            "png"
+        );
+        assert!(!out.path().join("guides/install.md").exists());
+        assert!(!out.path().join(".git").exists());
+
+        // The output directory inside the store is skipped however it's
+        // spelled.
+        let out = root.path().join("guides/../site");
+        StaticExport::new().write(&store, &out, layout).unwrap();
+        assert!(root.path().join("site/guides/logo.png").exists());
+        assert!(!root.path().join("site/site").exists());
+    }
+
+    #[test]
+    fn export_skips_page_files_of_the_mapping() {
+        let root = TestDir::new();
+        write_page(root.path(), "guides/index.md", "# Guides");
+        write_page(root.path(), "guides/README.md", "# Readme");
+        write_page(root.path(), "guides/install.markdown", "# Install");
+        let mapping = PageMapping::new()
+            .index_names(["index", "README"])
+            .extensions(["md", "markdown"]);
+        let options = StoreOptions::new().page_mapping(mapping);
+        let store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+
+        let out = TestDir::new();
+        StaticExport::new()
+            .write(&store, out.path(), layout)
+            .unwrap();
+        assert!(out.path().join("guides/index.html").exists());
+        assert!(out.path().join("guides/install.html").exists());
+        assert!(!out.path().join("guides/README.md").exists());
+        assert!(!out.path().join("guides/install.markdown").exists());
+    }
+
+    #[test]
+    fn export_reports_conflicts_and_template_errors() {
+        let root = TestDir::new();
+        write_page(root.path(), "guides.md", "# Guides");
+        write_page(root.path(), "guides/index.md", "# Guides index");
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        let out = TestDir::new();
+
+        let error = StaticExport::new()
+            .layout(ExportLayout::Directories)
+            .write(&store, out.path(), layout)
+            .unwrap_err();
+        assert!(
+            matches!(error, Error::OutputConflict { first, second, .. } if first == "/guides" && second == "/guides/")
+        );
+
+        write_page(root.path(), "guides/install.md", "# Install");
+        write_page(root.path(), "guides/install.html", "<p>Old</p>");
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        let error = StaticExport::new()
+            .write(&store, out.path(), layout)
+            .unwrap_err();
+        assert!(
+            matches!(error, Error::OutputConflict { first, second, .. } if first == "/guides/install" && second == "guides/install.html")
+        );
+
+        let error = StaticExport::new()
+            .write(&store, out.path(), |_: &ExportedPage<'_>| Err("broken"))
+            .unwrap_err();
+        assert!(matches!(error, Error::Template { url, .. } if url == "/guides"));
+    }
+}
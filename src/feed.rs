@@ -0,0 +1,508 @@
+//! Atom and RSS 2.0 feeds for a section of a store.
+
+use std::cmp::Reverse;
+
+use crate::{
+    Error, FlatPage, FlatPageStore, PageUrl, RenderOptions, Result,
+    error::UrlProblem,
+    util::{Timestamp, escape_html, percent_encode_path},
+};
+
+/// Frontmatter fields used by feed entries.
+#[derive(Debug, serde::Deserialize)]
+struct FeedFrontmatter {
+    /// Publication date.
+    date: Option<Timestamp>,
+    /// Author name.
+    author: Option<String>,
+    /// Excludes an unpublished page from the feed.
+    #[serde(default)]
+    draft: bool,
+}
+
+/// Content of feed entries
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum FeedContent {
+    /// Rendered [`FlatPage::summary`], falling back to the description
+    #[default]
+    Summary,
+    /// The whole rendered page body
+    Html,
+}
+
+/// Atom and RSS 2.0 feed generator
+///
+/// Entries are the pages under a URL prefix, such as `/blog/`, excluding the
+/// prefix page itself and pages with `draft: true`. Frontmatter `date`,
+/// `author` and `description` fields fill in the entry metadata, and pages
+/// without a `date` use the file modification time. Entries are ordered by
+/// date, newest first, then by URL. Relative links and images in the content
+/// are resolved against the page URL, as feed readers show it elsewhere.
+///
+/// Atom entries without an author, from the frontmatter or
+/// [`Feed::author`], are attributed to the feed title, as Atom requires one.
+///
+/// The output only depends on the page files, so unchanged pages produce
+/// byte-for-byte identical feeds.
+#[derive(Clone, Debug)]
+pub struct Feed {
+    /// Site URL without a trailing slash, such as `https://example.com`.
+    base_url: String,
+    /// Feed title.
+    title: String,
+    /// Folder URL of feed pages, validated when rendering.
+    prefix: String,
+    /// Feed description, required by RSS.
+    description: Option<String>,
+    /// Author of pages without an `author` field.
+    author: Option<String>,
+    /// Entry content.
+    content: FeedContent,
+    /// Options used to render entry content.
+    render_options: RenderOptions,
+    /// Maximum number of entries.
+    limit: Option<usize>,
+}
+
+/// A feed entry read from a page.
+struct Entry {
+    /// Absolute page URL.
+    url: String,
+    /// HTML-escaped plain-text title.
+    title: String,
+    /// Page description.
+    description: Option<String>,
+    /// Publication date, or the modification time of undated pages.
+    date: Timestamp,
+    /// Author name.
+    author: Option<String>,
+    /// Rendered HTML content.
+    content: String,
+}
+
+impl Feed {
+    /// Creates a feed of all pages of a site such as `https://example.com`.
+    pub fn new(base_url: impl Into<String>, title: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        base_url.truncate(base_url.trim_end_matches('/').len());
+        Self {
+            base_url,
+            title: title.into(),
+            prefix: "/".into(),
+            description: None,
+            author: None,
+            content: FeedContent::default(),
+            render_options: RenderOptions::default(),
+            limit: None,
+        }
+    }
+
+    /// Only includes pages under a folder URL, such as `/blog/`.
+    ///
+    /// Rendering fails with [`Error::InvalidUrl`] unless the prefix is a page
+    /// URL ending with `/`, so `/blog` can't match `/blogroll`.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets the feed description, the title by default.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the author of pages without an `author` frontmatter field.
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Sets the content of entries, [`FeedContent::Summary`] by default.
+    pub fn content(mut self, content: FeedContent) -> Self {
+        self.content = content;
+        self
+    }
+
+    /// Sets the options used to render entry content, such as
+    /// [`RawHtml::Escape`] for pages edited by untrusted contributors.
+    ///
+    /// [`RawHtml::Escape`]: crate::RawHtml::Escape
+    pub fn render_options(mut self, options: RenderOptions) -> Self {
+        self.render_options = options;
+        self
+    }
+
+    /// Limits the number of entries, keeping the newest ones.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Renders an Atom feed.
+    ///
+    /// `self_url` is the absolute URL the feed is served from.
+    pub fn render_atom(&self, store: &FlatPageStore, self_url: &str) -> Result<String> {
+        let prefix = self.parse_prefix(store)?;
+        let entries = self.entries(store, &prefix)?;
+        let link = escape_html(&self.link(&prefix)).into_owned();
+        let mut xml = String::new();
+        xml.push_str(&format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <feed xmlns=\"http://www.w3.org/2005/Atom\">\n  \
+             <title>{}</title>\n  \
+             <link href=\"{link}\"/>\n  \
+             <link rel=\"self\" href=\"{}\"/>\n  \
+             <id>{link}</id>\n  \
+             <updated>{}</updated>\n",
+            escape_html(&self.title),
+            escape_html(self_url),
+            updated(&entries).to_rfc3339(),
+        ));
+        if let Some(subtitle) = &self.description {
+            xml.push_str(&format!(
+                "  <subtitle>{}</subtitle>\n",
+                escape_html(subtitle)
+            ));
+        }
+        for entry in &entries {
+            let url = escape_html(&entry.url);
+            xml.push_str(&format!(
+                "  <entry>\n    \
+                 <title>{}</title>\n    \
+                 <link href=\"{url}\"/>\n    \
+                 <id>{url}</id>\n    \
+                 <updated>{}</updated>\n",
+                entry.title,
+                entry.date.to_rfc3339(),
+            ));
+            let author = entry.author.as_deref().unwrap_or(&self.title);
+            xml.push_str(&format!(
+                "    <author><name>{}</name></author>\n",
+                escape_html(author)
+            ));
+            if let Some(summary) = &entry.description {
+                xml.push_str(&format!(
+                    "    <summary>{}</summary>\n",
+                    escape_html(summary)
+                ));
+            }
+            xml.push_str(&format!(
+                "    <content type=\"html\">{}</content>\n  </entry>\n",
+                escape_html(&entry.content)
+            ));
+        }
+        xml.push_str("</feed>\n");
+        Ok(xml)
+    }
+
+    /// Renders an RSS 2.0 feed.
+    pub fn render_rss(&self, store: &FlatPageStore) -> Result<String> {
+        let prefix = self.parse_prefix(store)?;
+        let entries = self.entries(store, &prefix)?;
+        let mut xml = String::new();
+        xml.push_str(&format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <rss version=\"2.0\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n  \
+             <channel>\n    \
+             <title>{}</title>\n    \
+             <link>{}</link>\n    \
+             <description>{}</description>\n    \
+             <lastBuildDate>{}</lastBuildDate>\n",
+            escape_html(&self.title),
+            escape_html(&self.link(&prefix)),
+            escape_html(self.description.as_deref().unwrap_or(&self.title)),
+            updated(&entries).to_rfc2822(),
+        ));
+        for entry in &entries {
+            let url = escape_html(&entry.url);
+            xml.push_str(&format!(
+                "    <item>\n      \
+                 <title>{}</title>\n      \
+                 <link>{url}</link>\n      \
+                 <guid isPermaLink=\"true\">{url}</guid>\n",
+                entry.title,
+            ));
+            xml.push_str(&format!(
+                "      <pubDate>{}</pubDate>\n",
+                entry.date.to_rfc2822()
+            ));
+            if let Some(author) = &entry.author {
+                xml.push_str(&format!(
+                    "      <dc:creator>{}</dc:creator>\n",
+                    escape_html(author)
+                ));
+            }
+            xml.push_str(&format!(
+                "      <description>{}</description>\n    </item>\n",
+                escape_html(&entry.content)
+            ));
+        }
+        xml.push_str("  </channel>\n</rss>\n");
+        Ok(xml)
+    }
+
+    /// Validates the prefix as a folder URL of the store.
+    fn parse_prefix(&self, store: &FlatPageStore) -> Result<PageUrl> {
+        let prefix = PageUrl::parse_with(&self.prefix, &store.mapping().segment_policy)?;
+        if !prefix.is_index() {
+            return Err(Error::invalid_url(
+                &self.prefix,
+                UrlProblem::MissingTrailingSlash,
+            ));
+        }
+        Ok(prefix)
+    }
+
+    /// Returns the absolute URL of the prefix page.
+    fn link(&self, prefix: &PageUrl) -> String {
+        format!("{}{}", self.base_url, percent_encode_path(prefix.as_str()))
+    }
+
+    /// Reads the pages under the prefix, newest first.
+    fn entries(&self, store: &FlatPageStore, prefix: &PageUrl) -> Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+        for (url, _, path) in store.page_files() {
+            let url = url.as_str();
+            if url == prefix.as_str() || !url.starts_with(prefix.as_str()) {
+                continue;
+            }
+            let Some(page) = FlatPage::<FeedFrontmatter>::by_path(path)? else {
+                continue;
+            };
+            if page.extra.draft {
+                continue;
+            }
+            // The modification time is only missing on platforms without it.
+            let Some(date) = page
+                .extra
+                .date
+                .or_else(|| page.validators.modified.map(Timestamp::from))
+            else {
+                continue;
+            };
+            let render_options = self
+                .render_options
+                .clone()
+                .absolute_links(&self.base_url, &percent_encode_path(url));
+            let content = match self.content {
+                FeedContent::Summary => match page.summary() {
+                    Some(summary) => summary.html_with(&render_options),
+                    None => page
+                        .description
+                        .as_deref()
+                        .map(|description| escape_html(description).into_owned())
+                        .unwrap_or_default(),
+                },
+                FeedContent::Html => page.html_with(&render_options),
+            };
+            entries.push(Entry {
+                url: format!("{}{}", self.base_url, percent_encode_path(url)),
+                title: escape_html(&page.title_text()).into_owned(),
+                description: page.description,
+                date,
+                author: page.extra.author.or_else(|| self.author.clone()),
+                content,
+            });
+        }
+        // Pages come sorted by URL, and the stable sort keeps that order for
+        // equal dates.
+        entries.sort_by_key(|entry| Reverse(entry.date));
+        if let Some(limit) = self.limit {
+            entries.truncate(limit);
+        }
+        Ok(entries)
+    }
+}
+
+/// Returns the date of the newest entry.
+fn updated(entries: &[Entry]) -> Timestamp {
+    entries
+        .iter()
+        .map(|entry| entry.date)
+        .max()
+        .unwrap_or(Timestamp::EPOCH)
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{
+        RawHtml,
+        test_helpers::{TestDir, write_page},
+    };
+
+    fn store() -> (TestDir, FlatPageStore) {
+        let root = TestDir::new();
+        write_page(root.path(), "blog/index.md", "# Blog");
+        write_page(
+            root.path(),
+            "blog/first.md",
+            "---\ndate: 2024-01-02\nauthor: Ann\ndescription: First & best\n---\n# *First*\n\nIntro.\n\nMore.",
+        );
+        write_page(
+            root.path(),
+            "blog/second.md",
+            "---\ndate: 2024-02-03T10:00:00Z\n---\n# Second\n\nHello <b>world</b>.",
+        );
+        write_page(
+            root.path(),
+            "blog/undated.md",
+            "# Undated\n\n[First](first) ![Logo](../logo.png)",
+        );
+        fs::File::options()
+            .write(true)
+            .open(root.path().join("blog/undated.md"))
+            .unwrap()
+            .set_modified(Timestamp::parse("2023-12-01").unwrap().into())
+            .unwrap();
+        write_page(root.path(), "blogroll.md", "# Blogroll");
+        write_page(
+            root.path(),
+            "blog/draft.md",
+            "---\ndate: 2024-03-01\ndraft: true\n---\n# Draft",
+        );
+        write_page(root.path(), "about.md", "# About");
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        (root, store)
+    }
+
+    #[test]
+    fn feed_renders_atom() {
+        let (_root, store) = store();
+        let feed = Feed::new("https://example.com/", "Blog & news")
+            .prefix("/blog/")
+            .author("Site")
+            .limit(2);
+        let atom = feed
+            .render_atom(&store, "https://example.com/blog/atom.xml")
+            .unwrap();
+        assert_eq!(
+            atom,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Blog &amp; news</title>
+  <link href="https://example.com/blog/"/>
+  <link rel="self" href="https://example.com/blog/atom.xml"/>
+  <id>https://example.com/blog/</id>
+  <updated>2024-02-03T10:00:00Z</updated>
+  <entry>
+    <title>Second</title>
+    <link href="https://example.com/blog/second"/>
+    <id>https://example.com/blog/second</id>
+    <updated>2024-02-03T10:00:00Z</updated>
+    <author><name>Site</name></author>
+    <content type="html">&lt;p&gt;Hello &lt;b&gt;world&lt;/b&gt;.&lt;/p&gt;
+</content>
+  </entry>
+  <entry>
+    <title>First</title>
+    <link href="https://example.com/blog/first"/>
+    <id>https://example.com/blog/first</id>
+    <updated>2024-01-02T00:00:00Z</updated>
+    <author><name>Ann</name></author>
+    <summary>First &amp; best</summary>
+    <content type="html">&lt;p&gt;Intro.&lt;/p&gt;
+</content>
+  </entry>
+</feed>
+"#
+        );
+        assert_eq!(
+            atom,
+            feed.render_atom(&store, "https://example.com/blog/atom.xml")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn feed_renders_rss() {
+        let (_root, store) = store();
+        let rss = Feed::new("https://example.com", "Blog")
+            .prefix("/blog/")
+            .content(FeedContent::Html)
+            .render_rss(&store)
+            .unwrap();
+        assert!(rss.starts_with(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <title>Blog</title>
+    <link>https://example.com/blog/</link>
+    <description>Blog</description>
+    <lastBuildDate>Sat, 03 Feb 2024 10:00:00 +0000</lastBuildDate>
+    <item>
+      <title>Second</title>
+"#
+        ));
+        assert!(rss.contains(
+            r#"      <link>https://example.com/blog/first</link>
+      <guid isPermaLink="true">https://example.com/blog/first</guid>
+      <pubDate>Tue, 02 Jan 2024 00:00:00 +0000</pubDate>
+      <dc:creator>Ann</dc:creator>
+      <description>&lt;h1&gt;&lt;em&gt;First&lt;/em&gt;&lt;/h1&gt;"#
+        ));
+        assert!(rss.contains(
+            r#"      <pubDate>Fri, 01 Dec 2023 00:00:00 +0000</pubDate>
+      <description>&lt;h1&gt;Undated&lt;/h1&gt;
+&lt;p&gt;&lt;a href=&quot;https://example.com/blog/first&quot;&gt;First&lt;/a&gt; &lt;img src=&quot;https://example.com/logo.png&quot;"#
+        ));
+        assert!(!rss.contains("Blogroll"));
+        assert!(!rss.contains("Draft"));
+        assert!(!rss.contains("About"));
+        assert!(rss.ends_with("</item>\n  </channel>\n</rss>\n"));
+    }
+
+    #[test]
+    fn feed_attributes_atom_entries_without_author_to_the_feed() {
+        let (_root, store) = store();
+        let atom = Feed::new("https://example.com", "Blog")
+            .prefix("/blog/")
+            .render_atom(&store, "https://example.com/blog/atom.xml")
+            .unwrap();
+        assert_eq!(atom.matches("<entry>").count(), 3);
+        assert_eq!(
+            atom.matches("<author><name>Blog</name></author>").count(),
+            2
+        );
+        assert_eq!(atom.matches("<author><name>Ann</name></author>").count(), 1);
+    }
+
+    #[test]
+    fn feed_rejects_invalid_prefixes() {
+        let (_root, store) = store();
+        for (prefix, expected) in [
+            ("/blog", UrlProblem::MissingTrailingSlash),
+            ("blog/", UrlProblem::MissingLeadingSlash),
+            ("/blog//", UrlProblem::EmptySegment),
+        ] {
+            let feed = Feed::new("https://example.com", "Blog").prefix(prefix);
+            assert!(matches!(
+                feed.render_rss(&store),
+                Err(Error::InvalidUrl { url, problem }) if url == prefix && problem == expected
+            ));
+            assert!(
+                feed.render_atom(&store, "https://example.com/atom.xml")
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn feed_renders_with_render_options() {
+        let (_root, store) = store();
+        let feed = Feed::new("https://example.com", "Blog")
+            .prefix("/blog/")
+            .content(FeedContent::Html);
+        let rss = feed.render_rss(&store).unwrap();
+        assert!(rss.contains("Hello &lt;b&gt;world&lt;/b&gt;."));
+        let rss = feed
+            .render_options(RenderOptions::new().raw_html(RawHtml::Escape))
+            .render_rss(&store)
+            .unwrap();
+        assert!(rss.contains("Hello &amp;lt;b&amp;gt;world&amp;lt;/b&amp;gt;."));
+    }
+}
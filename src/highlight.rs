@@ -0,0 +1,190 @@
+//! Server-side syntax highlighting of fenced code blocks.
+
+use std::sync::OnceLock;
+
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    html::{
+        ClassStyle, ClassedHTMLGenerator, IncludeBackground,
+        append_highlighted_html_for_styled_line, css_for_theme_with_class_style,
+    },
+    parsing::{SyntaxReference, SyntaxSet},
+    util::LinesWithEndings,
+};
+
+use crate::util::escape_html;
+
+/// Prefix of the CSS classes emitted by [`Highlighter::classed`].
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+/// Fence languages that bundled grammars know under another name.
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[("console", "bash"), ("shell", "bash")];
+
+/// Highlights fenced code blocks using bundled grammars and themes.
+///
+/// Code blocks without a language or with an unknown language are rendered
+/// as usual.
+#[derive(Clone, Debug)]
+pub struct Highlighter {
+    /// How the highlighted spans are styled.
+    style: HighlightStyle,
+}
+
+/// Styling of highlighted spans.
+#[derive(Clone, Debug)]
+enum HighlightStyle {
+    /// `class` attributes, styled by [`Highlighter::css`].
+    Classed,
+    /// `style` attributes using the named theme.
+    Inline(&'static Theme),
+}
+
+impl Highlighter {
+    /// Creates a highlighter that emits `hl-`-prefixed CSS classes.
+    ///
+    /// Use [`Highlighter::css`] to generate the matching stylesheet.
+    pub fn classed() -> Self {
+        Self {
+            style: HighlightStyle::Classed,
+        }
+    }
+
+    /// Creates a highlighter that emits inline `style` attributes.
+    ///
+    /// Returns `None` for unknown themes, see [`Highlighter::themes`].
+    pub fn inline(theme: &str) -> Option<Self> {
+        Some(Self {
+            style: HighlightStyle::Inline(theme_set().themes.get(theme)?),
+        })
+    }
+
+    /// Iterates over the names of the bundled themes.
+    pub fn themes() -> impl Iterator<Item = &'static str> {
+        theme_set().themes.keys().map(String::as_str)
+    }
+
+    /// Returns the stylesheet for [`Highlighter::classed`] output.
+    ///
+    /// Returns `None` for unknown themes, see [`Highlighter::themes`].
+    pub fn css(theme: &str) -> Option<String> {
+        let theme = theme_set().themes.get(theme)?;
+        css_for_theme_with_class_style(theme, CLASS_STYLE).ok()
+    }
+
+    /// Renders a code block, returns `None` if the language is unknown.
+    pub(crate) fn highlight(&self, language: &str, code: &str) -> Option<String> {
+        let syntax = find_syntax(language)?;
+        let spans = match self.style {
+            HighlightStyle::Classed => classed_spans(syntax, code)?,
+            HighlightStyle::Inline(theme) => inline_spans(syntax, theme, code)?,
+        };
+        let pre = match self.style {
+            HighlightStyle::Classed => "<pre class=\"hl-code\">".to_string(),
+            HighlightStyle::Inline(theme) => match theme.settings.background {
+                Some(c) => format!(
+                    "<pre style=\"background-color:#{:02x}{:02x}{:02x};\">",
+                    c.r, c.g, c.b
+                ),
+                None => "<pre>".to_string(),
+            },
+        };
+        Some(format!(
+            "{pre}<code class=\"language-{}\">{spans}</code></pre>\n",
+            escape_html(language)
+        ))
+    }
+}
+
+/// Looks up a bundled grammar by a fence language token.
+fn find_syntax(language: &str) -> Option<&'static SyntaxReference> {
+    let language = LANGUAGE_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(language))
+        .map_or(language, |(_, name)| name);
+    syntax_set().find_syntax_by_token(language)
+}
+
+/// Highlights code into spans with CSS classes.
+fn classed_spans(syntax: &SyntaxReference, code: &str) -> Option<String> {
+    let mut generator =
+        ClassedHTMLGenerator::new_with_class_style(syntax, syntax_set(), CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .ok()?;
+    }
+    Some(generator.finalize())
+}
+
+/// Highlights code into spans with inline styles.
+fn inline_spans(syntax: &SyntaxReference, theme: &Theme, code: &str) -> Option<String> {
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut html = String::new();
+    for line in LinesWithEndings::from(code) {
+        let regions = highlighter.highlight_line(line, syntax_set()).ok()?;
+        append_highlighted_html_for_styled_line(&regions, IncludeBackground::No, &mut html).ok()?;
+    }
+    Some(html)
+}
+
+/// Returns the lazily loaded bundled grammars.
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(two_face::syntax::extra_newlines)
+}
+
+/// Returns the lazily loaded bundled themes.
+fn theme_set() -> &'static ThemeSet {
+    static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+    THEME_SET.get_or_init(ThemeSet::load_defaults)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_classed() {
+        let html = Highlighter::classed()
+            .highlight("rust", "fn main() {}\n")
+            .unwrap();
+        assert!(html.starts_with("<pre class=\"hl-code\"><code class=\"language-rust\">"));
+        assert!(html.contains("<span class=\"hl-storage hl-type hl-function hl-rust\">fn</span>"));
+
+        assert!(
+            Highlighter::classed()
+                .highlight("toml", "a = 1\n")
+                .is_some()
+        );
+        assert!(Highlighter::classed().highlight("shell", "ls\n").is_some());
+        assert!(
+            Highlighter::classed()
+                .highlight("unknown", "foo\n")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_highlight_inline() {
+        assert!(Highlighter::inline("unknown").is_none());
+
+        let html = Highlighter::inline("InspiredGitHub")
+            .unwrap()
+            .highlight("rust", "fn main() {}\n")
+            .unwrap();
+        assert!(html.starts_with("<pre style=\"background-color:#ffffff;\">"));
+        assert!(html.contains("<span style=\""));
+    }
+
+    #[test]
+    fn test_css() {
+        assert!(Highlighter::themes().any(|theme| theme == "base16-ocean.dark"));
+        assert!(
+            Highlighter::css("base16-ocean.dark")
+                .unwrap()
+                .contains(".hl-code")
+        );
+        assert!(Highlighter::css("unknown").is_none());
+    }
+}
@@ -4,14 +4,57 @@
 #[cfg(not(any(feature = "json", feature = "toml", feature = "yaml")))]
 compile_error!("enable at least one frontmatter feature: json, toml, yaml");
 
+#[cfg(feature = "axum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axum")))]
+pub mod axum;
+mod cache;
+mod client_search;
+mod context;
 mod error;
+mod export;
+mod feed;
+#[cfg(feature = "highlight")]
+#[cfg_attr(docsrs, doc(cfg(feature = "highlight")))]
+mod highlight;
+mod links;
+mod lookup;
+mod mapping;
 mod markdown;
 mod page;
+mod redirect;
+#[cfg(feature = "sanitize")]
+mod sanitize;
+mod search;
+mod segment;
+mod sitemap;
+mod stats;
 mod store;
+mod suggest;
 #[cfg(test)]
 mod test_helpers;
+mod tokenizer;
+mod url;
 pub(crate) mod util;
 
-pub use error::{Error, Result};
-pub use page::FlatPage;
-pub use store::{FlatPageMeta, FlatPageStore};
+pub use cache::CacheValidators;
+pub use client_search::{ClientSearchIndex, ClientSearchPage};
+pub use context::{Heading, NavLink, PageContext};
+pub use error::{Error, PathProblem, Result, RuleProblem, UrlProblem};
+pub use export::{ExportLayout, ExportedPage, StaticExport};
+pub use feed::{Feed, FeedContent};
+#[cfg(feature = "highlight")]
+#[cfg_attr(docsrs, doc(cfg(feature = "highlight")))]
+pub use highlight::Highlighter;
+pub use links::{BrokenLink, LinkProblem};
+pub use lookup::Lookup;
+pub use mapping::PageMapping;
+pub use markdown::{RawHtml, RenderOptions};
+pub use page::{FlatPage, Summary};
+pub use redirect::BrokenRedirect;
+pub use search::SearchHit;
+pub use segment::SegmentPolicy;
+pub use sitemap::{Sitemap, SitemapFile};
+pub use stats::PageStats;
+pub use store::{FlatPageMeta, FlatPageStore, StoreOptions};
+pub use tokenizer::Tokenizer;
+pub use url::{PageUrl, path_for_url, path_for_url_with, url_for_path, url_for_path_with};
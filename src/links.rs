@@ -0,0 +1,252 @@
+//! Internal link and anchor checking for a page store.
+
+use std::{collections::HashMap, fs, io, path::Path};
+
+use serde::de::IgnoredAny;
+
+#[cfg(doc)]
+use crate::FlatPageStore;
+use crate::{
+    Error, Result, SegmentPolicy,
+    markdown::{markdown_anchors, markdown_links},
+    util::{is_external_url, percent_decode},
+};
+
+/// A link pointing at a missing page or anchor.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BrokenLink {
+    /// URL of the page containing the link
+    pub source: String,
+    /// 1-based line number of the link in the source file
+    pub line: usize,
+    /// Link destination as written in Markdown
+    pub target: String,
+    /// What is wrong with the link
+    pub problem: LinkProblem,
+}
+
+/// The reason a link is reported by [`FlatPageStore::check_links`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LinkProblem {
+    /// The link points at a URL that is not in the store.
+    MissingPage,
+    /// The target page exists, but has no matching `#fragment`.
+    MissingAnchor,
+}
+
+/// Links and anchors of a single page.
+struct PageLinks {
+    /// Number of lines occupied by frontmatter.
+    line_offset: usize,
+    /// Markdown body of the page.
+    body: String,
+    /// Link destinations with their byte offsets in the body.
+    links: Vec<(String, usize)>,
+}
+
+/// Checks links of the given pages, see [`FlatPageStore::check_links`].
+pub(crate) fn check_links<'a>(
+    root: &Path,
+    policy: &SegmentPolicy,
+    files: impl Iterator<Item = (&'a str, &'a Path)>,
+) -> Result<Vec<BrokenLink>> {
+    let mut pages = HashMap::new();
+    let mut anchors = HashMap::new();
+    for (url, path) in files {
+        let Some(page) = read_page_links(path)? else {
+            continue;
+        };
+        anchors.insert(url, markdown_anchors(&page.body));
+        pages.insert(url, page);
+    }
+
+    let mut broken = Vec::new();
+    for (source, page) in &pages {
+        for (target, offset) in &page.links {
+            let Some((path, fragment)) = resolve_link(source, target) else {
+                continue;
+            };
+            // Links to Unicode pages may be percent-encoded or decomposed.
+            let path = percent_decode(&path).unwrap_or(path);
+            let path = policy.normalize(&path);
+            let problem = match anchors.get(path.as_ref()) {
+                Some(page_anchors) => match fragment {
+                    Some(fragment) if !page_anchors.contains(fragment) => {
+                        LinkProblem::MissingAnchor
+                    }
+                    _ => continue,
+                },
+                None if root.join(path.trim_start_matches('/')).is_file() => continue,
+                None => LinkProblem::MissingPage,
+            };
+            broken.push(BrokenLink {
+                source: source.to_string(),
+                line: page.line_offset + page.body[..*offset].matches('\n').count() + 1,
+                target: target.clone(),
+                problem,
+            });
+        }
+    }
+    broken.sort_by(|a, b| (&a.source, a.line).cmp(&(&b.source, b.line)));
+    Ok(broken)
+}
+
+/// Reads a page file and collects its links.
+///
+/// Returns `Ok(None)` when the file disappeared since the store was built.
+fn read_page_links(path: &Path) -> Result<Option<PageLinks>> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(Error::read_file(e, path)),
+    };
+    let (IgnoredAny, body) = markdown_frontmatter::parse::<IgnoredAny>(&content)
+        .map_err(|e| Error::parse_frontmatter(e, path))?;
+    let line_offset = content[..content.len() - body.len()].matches('\n').count();
+    Ok(Some(PageLinks {
+        line_offset,
+        links: markdown_links(body),
+        body: body.to_string(),
+    }))
+}
+
+/// Resolves a link destination against the URL of the page containing it.
+///
+/// Returns the target path with the fragment, if any, or `None` for external
+/// links.
+fn resolve_link<'a>(source: &str, target: &'a str) -> Option<(String, Option<&'a str>)> {
+    if is_external_url(target) {
+        return None;
+    }
+
+    let (target, fragment) = match target.split_once('#') {
+        Some((target, fragment)) => (target, Some(fragment)),
+        None => (target, None),
+    };
+    let target = target.split_once('?').map_or(target, |(target, _)| target);
+    if target.is_empty() {
+        return Some((source.to_string(), fragment));
+    }
+
+    let mut segments: Vec<&str> = if target.starts_with('/') {
+        Vec::new()
+    } else {
+        let dir = &source[..source.rfind('/').unwrap_or(0)];
+        dir.split('/').filter(|s| !s.is_empty()).collect()
+    };
+    let mut is_dir = false;
+    for segment in target.split('/') {
+        is_dir = true;
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => {
+                segments.push(segment);
+                is_dir = false;
+            }
+        }
+    }
+
+    let mut path = String::from("/");
+    path.push_str(&segments.join("/"));
+    if is_dir && !segments.is_empty() {
+        path.push('/');
+    }
+    Some((path, fragment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        FlatPageStore,
+        test_helpers::{TestDir, write_page},
+    };
+
+    #[test]
+    fn test_resolve_link() {
+        assert_eq!(resolve_link("/guides/install", "https://x.org"), None);
+        assert_eq!(resolve_link("/guides/install", "mailto:a@b.c"), None);
+        assert_eq!(resolve_link("/guides/install", "//x.org/foo"), None);
+        assert_eq!(
+            resolve_link("/guides/install", "#usage"),
+            Some(("/guides/install".to_string(), Some("usage")))
+        );
+        assert_eq!(
+            resolve_link("/guides/install", "setup?ref=1#top"),
+            Some(("/guides/setup".to_string(), Some("top")))
+        );
+        assert_eq!(
+            resolve_link("/guides/", "setup"),
+            Some(("/guides/setup".to_string(), None))
+        );
+        assert_eq!(
+            resolve_link("/guides/install", "../"),
+            Some(("/".to_string(), None))
+        );
+        assert_eq!(
+            resolve_link("/guides/install", "../about/team"),
+            Some(("/about/team".to_string(), None))
+        );
+        assert_eq!(
+            resolve_link("/guides/install", "/blog/"),
+            Some(("/blog/".to_string(), None))
+        );
+    }
+
+    #[test]
+    fn check_links_reports_missing_pages_and_anchors() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# Home\n\n[Guides](/guides/)");
+        write_page(
+            root.path(),
+            "guides/index.md",
+            "# Guides {#top}\n\n[Install](install#usage)\n[Top](#top)\n[Missing](missing)\n[Bad \
+             anchor](#nope)\n![Logo](/logo.png)\n[Asset](/files/report.pdf)\n[External](https://example.com)",
+        );
+        write_page(
+            root.path(),
+            "guides/install.md",
+            "# Install\n\n## Usage {#usage}",
+        );
+        write_page(root.path(), "files/report.pdf", "");
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        assert_eq!(
+            store.check_links().unwrap(),
+            vec![
+                BrokenLink {
+                    source: "/guides/".to_string(),
+                    line: 5,
+                    target: "missing".to_string(),
+                    problem: LinkProblem::MissingPage,
+                },
+                BrokenLink {
+                    source: "/guides/".to_string(),
+                    line: 6,
+                    target: "#nope".to_string(),
+                    problem: LinkProblem::MissingAnchor,
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn check_links_counts_frontmatter_lines() {
+        let root = TestDir::new();
+        write_page(
+            root.path(),
+            "index.md",
+            "---\ntitle: Home\n---\n\n[Missing](/missing)",
+        );
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        let broken = store.check_links().unwrap();
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].line, 5);
+    }
+}
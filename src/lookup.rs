@@ -0,0 +1,40 @@
+//! Lenient page lookups with redirects to canonical URLs.
+
+use crate::{FlatPageMeta, PageUrl};
+
+/// Result of [`FlatPageStore::lookup`]
+///
+/// [`FlatPageStore::lookup`]: crate::FlatPageStore::lookup
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Lookup<'a> {
+    /// The page exists at the requested URL.
+    Found {
+        /// Canonical URL of the page
+        url: &'a PageUrl,
+        /// Cached metadata of the page
+        meta: &'a FlatPageMeta,
+    },
+    /// The page exists at another URL, which the client should be
+    /// redirected to.
+    Redirect(PageUrl),
+    /// A rule of the `_redirects` file in the pages root matched.
+    Rule {
+        /// Destination URL, either a path or a URL of another site
+        location: String,
+        /// HTTP redirect status, such as 301
+        status: u16,
+    },
+    /// There's no such page, or the URL is invalid.
+    NotFound,
+}
+
+impl<'a> Lookup<'a> {
+    /// Returns the metadata of a found page.
+    pub fn found(&self) -> Option<&'a FlatPageMeta> {
+        match self {
+            Self::Found { meta, .. } => Some(meta),
+            _ => None,
+        }
+    }
+}
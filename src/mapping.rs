@@ -0,0 +1,130 @@
+//! Configurable mapping between page URLs and file names.
+
+use crate::SegmentPolicy;
+
+/// How page URLs map to files, see [`StoreOptions::page_mapping`] and
+/// [`FlatPage::by_url_with`]
+///
+/// By default `/guides/` is `guides/index.md` and `/guides/install` is
+/// `guides/install.md`. Index file names and extensions are tried in the given
+/// order, so with `README` as a second index name, `guides/index.md` wins over
+/// `guides/README.md` when both exist, and the other file is ignored. Index
+/// names take precedence over extensions.
+///
+/// [`StoreOptions::page_mapping`]: crate::StoreOptions::page_mapping
+/// [`FlatPage::by_url_with`]: crate::FlatPage::by_url_with
+#[derive(Clone, Debug)]
+pub struct PageMapping {
+    /// Characters allowed in URL segments and file names.
+    pub(crate) segment_policy: SegmentPolicy,
+    /// File stems of folder index pages, most preferred first.
+    pub(crate) index_names: Vec<String>,
+    /// Extensions of page files without the dot, most preferred first.
+    pub(crate) extensions: Vec<String>,
+}
+
+impl Default for PageMapping {
+    /// Maps URLs to `index.md` and `*.md` files with ASCII segments.
+    fn default() -> Self {
+        Self {
+            segment_policy: SegmentPolicy::default(),
+            index_names: vec!["index".into()],
+            extensions: vec!["md".into()],
+        }
+    }
+}
+
+impl PageMapping {
+    /// Creates the default mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the characters allowed in URL segments and file names,
+    /// [`SegmentPolicy::Ascii`] by default.
+    pub fn segment_policy(mut self, policy: SegmentPolicy) -> Self {
+        self.segment_policy = policy;
+        self
+    }
+
+    /// Sets the file stems of folder index pages, such as `index` and
+    /// `README`, most preferred first. `index` by default.
+    pub fn index_names<S: AsRef<str>>(mut self, names: impl IntoIterator<Item = S>) -> Self {
+        self.index_names = names.into_iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Sets the extensions of page files without the dot, such as `md` and
+    /// `markdown`, most preferred first. `md` by default.
+    pub fn extensions<S: AsRef<str>>(mut self, extensions: impl IntoIterator<Item = S>) -> Self {
+        self.extensions = extensions
+            .into_iter()
+            .map(|s| s.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    /// Splits a page file name into its stem and the preference of its
+    /// extension, or returns `None` for other files.
+    pub(crate) fn split_extension<'a>(&self, file_name: &'a str) -> Option<(&'a str, usize)> {
+        self.extensions.iter().enumerate().find_map(|(rank, ext)| {
+            let stem = file_name.strip_suffix(ext.as_str())?.strip_suffix('.')?;
+            (!stem.is_empty()).then_some((stem, rank))
+        })
+    }
+
+    /// Returns the preference of an index file stem, or `None` if the stem
+    /// isn't an index name.
+    pub(crate) fn index_rank(&self, stem: &str) -> Option<usize> {
+        self.index_names.iter().position(|name| name == stem)
+    }
+
+    /// Returns the preference of a page file name among files mapping to the
+    /// same URL, lower first, or `None` for other files.
+    pub(crate) fn preference(&self, file_name: &str) -> Option<(usize, usize)> {
+        let (stem, ext_rank) = self.split_extension(file_name)?;
+        Some((self.index_rank(stem).unwrap_or_default(), ext_rank))
+    }
+
+    /// Returns the file names of an index page, most preferred first.
+    pub(crate) fn index_files(&self) -> impl Iterator<Item = String> + '_ {
+        self.index_names
+            .iter()
+            .flat_map(|name| self.page_files(name))
+    }
+
+    /// Returns the file names of a page, most preferred first.
+    pub(crate) fn page_files<'a>(&'a self, stem: &'a str) -> impl Iterator<Item = String> + 'a {
+        self.extensions
+            .iter()
+            .map(move |ext| format!("{stem}.{ext}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preference() {
+        let mapping = PageMapping::new()
+            .index_names(["index", "README"])
+            .extensions(["md", "markdown"]);
+        assert_eq!(
+            mapping.split_extension("install.markdown"),
+            Some(("install", 1))
+        );
+        assert_eq!(mapping.split_extension("v1.2.md"), Some(("v1.2", 0)));
+        assert_eq!(mapping.split_extension(".md"), None);
+        assert_eq!(mapping.split_extension("notes.txt"), None);
+
+        assert_eq!(mapping.preference("index.md"), Some((0, 0)));
+        assert_eq!(mapping.preference("index.markdown"), Some((0, 1)));
+        assert_eq!(mapping.preference("README.md"), Some((1, 0)));
+        assert_eq!(mapping.preference("install.markdown"), Some((0, 1)));
+        assert_eq!(
+            mapping.index_files().collect::<Vec<_>>(),
+            ["index.md", "index.markdown", "README.md", "README.markdown"]
+        );
+    }
+}
@@ -1,8 +1,117 @@
-//! Markdown title extraction and HTML rendering helpers.
+//! Markdown title extraction, link collection and HTML rendering helpers.
 
-use std::ops::Range;
+use std::{borrow::Cow, collections::HashSet, ops::Range};
 
-use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd, html, utils::TextMergeWithOffset};
+use pulldown_cmark::{
+    CowStr, Event, Options, Parser, Tag, TagEnd, html, utils::TextMergeWithOffset,
+};
+
+#[cfg(doc)]
+use crate::FlatPage;
+#[cfg(feature = "highlight")]
+use crate::Highlighter;
+use crate::{
+    Heading,
+    util::{is_external_url, url_scheme},
+};
+
+/// Link schemes kept by [`RawHtml::Escape`].
+const SAFE_LINK_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Options for [`FlatPage::html_with`].
+///
+/// The default options render the same HTML as [`FlatPage::html`].
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    /// Treatment of raw HTML in Markdown.
+    raw_html: RawHtml,
+    /// Highlighter for fenced code blocks.
+    #[cfg(feature = "highlight")]
+    highlighter: Option<Highlighter>,
+    /// Site URL and percent-encoded page URL that relative link and image
+    /// destinations are resolved against, for HTML shown outside the site.
+    link_base: Option<(String, String)>,
+    /// Whether trailing `{#id .class}` blocks of headings are parsed.
+    heading_attributes: bool,
+}
+
+/// Treatment of raw HTML in Markdown, see [`RenderOptions::raw_html`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RawHtml {
+    /// Passes raw HTML through unchanged. Only use it for trusted content.
+    #[default]
+    Allow,
+    /// Escapes raw HTML so it shows up as text, and drops link and image
+    /// destinations with schemes other than `http`, `https` and `mailto`.
+    Escape,
+    /// Cleans the rendered HTML with an allowlist, removing scripts,
+    /// event-handler attributes, `javascript:` URLs and other unsafe markup.
+    #[cfg(feature = "sanitize")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sanitize")))]
+    Sanitize,
+}
+
+impl RenderOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the treatment of raw HTML, [`RawHtml::Allow`] by default.
+    ///
+    /// Use [`RawHtml::Escape`] or `RawHtml::Sanitize` (with the `sanitize`
+    /// feature) for content edited by untrusted contributors.
+    pub fn raw_html(mut self, raw_html: RawHtml) -> Self {
+        self.raw_html = raw_html;
+        self
+    }
+
+    /// Parses a trailing `{#id .class}` block of headings into attributes of
+    /// the heading, off by default.
+    ///
+    /// Otherwise such blocks are rendered as text, so `# Set {a b}` keeps its
+    /// braces. Page titles are always taken from the unparsed heading line.
+    pub fn heading_attributes(mut self, enabled: bool) -> Self {
+        self.heading_attributes = enabled;
+        self
+    }
+
+    /// Returns the parser extensions enabled by the options.
+    pub(crate) fn parser_options(&self) -> Options {
+        let mut options = parser_options();
+        options.set(Options::ENABLE_HEADING_ATTRIBUTES, self.heading_attributes);
+        options
+    }
+
+    /// Returns the prefix the rendered HTML gives to heading ids.
+    pub(crate) fn id_prefix(&self) -> &'static str {
+        #[cfg(feature = "sanitize")]
+        if self.raw_html == RawHtml::Sanitize {
+            return crate::sanitize::ID_PREFIX;
+        }
+        ""
+    }
+
+    /// Resolves relative link and image destinations against a page, such as
+    /// `/blog/first` of `https://example.com`, for HTML shown outside the
+    /// site, such as in feed readers.
+    pub(crate) fn absolute_links(mut self, base_url: &str, page_url: &str) -> Self {
+        self.link_base = Some((base_url.to_string(), page_url.to_string()));
+        self
+    }
+
+    /// Highlights fenced code blocks with a known language.
+    ///
+    /// With `RawHtml::Sanitize`, classes are emitted even by an inline
+    /// highlighter, as the sanitizer removes `style` attributes.
+    #[cfg(feature = "highlight")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "highlight")))]
+    pub fn highlight(mut self, highlighter: Highlighter) -> Self {
+        self.highlighter = Some(highlighter);
+        self
+    }
+}
 
 /// Uses the first non-empty line as the page title.
 ///
@@ -17,9 +126,187 @@ pub(crate) fn title_from_markdown(body: &str) -> &str {
     atx_heading_title(line).unwrap_or_else(|| line.trim())
 }
 
+/// Renders a single line of inline Markdown, such as a title, to HTML.
+///
+/// Block-level markup is ignored, so there's no `<p>` wrapper.
+pub(crate) fn inline_markdown_to_html(text: &str) -> String {
+    let text = escape_block_start(text);
+    let mut html = String::new();
+    html::push_html(&mut html, inline_events(&text));
+    html
+}
+
+/// Strips inline Markdown from a single line, such as a title.
+///
+/// Entities are decoded, raw HTML is dropped and line breaks become spaces.
+pub(crate) fn inline_markdown_to_text(text: &str) -> String {
+    let text = escape_block_start(text);
+    let mut plain = String::new();
+    for event in inline_events(&text) {
+        match event {
+            Event::Text(text) | Event::Code(text) => plain.push_str(&text),
+            Event::SoftBreak | Event::HardBreak => plain.push(' '),
+            _ => {}
+        }
+    }
+    plain
+}
+
+/// Converts Markdown to plain text.
+///
+/// Markup and raw HTML are dropped, entities are decoded and blocks are
+/// separated by blank lines. List items and table rows go on separate lines.
+pub(crate) fn markdown_to_text(text: &str, code_blocks: bool) -> String {
+    let mut plain = String::new();
+    let mut pending_break = "";
+    let mut in_code_block = false;
+    for event in Parser::new_ext(text, parser_options()) {
+        let text = match event {
+            Event::Text(_) if in_code_block && !code_blocks => continue,
+            Event::Text(text) if in_code_block => CowStr::from(text.trim_end().to_string()),
+            Event::Text(text) | Event::Code(text) => text,
+            Event::SoftBreak => CowStr::Borrowed(" "),
+            Event::HardBreak => CowStr::Borrowed("\n"),
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code_block = true;
+                continue;
+            }
+            Event::End(end) => {
+                let block_break = match end {
+                    TagEnd::CodeBlock => {
+                        in_code_block = false;
+                        "\n\n"
+                    }
+                    TagEnd::Paragraph
+                    | TagEnd::Heading(_)
+                    | TagEnd::BlockQuote(_)
+                    | TagEnd::List(_)
+                    | TagEnd::Table
+                    | TagEnd::FootnoteDefinition => "\n\n",
+                    TagEnd::Item | TagEnd::TableHead | TagEnd::TableRow => "\n",
+                    TagEnd::TableCell => " ",
+                    _ => "",
+                };
+                if block_break.len() > pending_break.len() {
+                    pending_break = block_break;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        if !plain.is_empty() {
+            plain.push_str(pending_break);
+        }
+        pending_break = "";
+        plain.push_str(&text);
+    }
+    plain
+}
+
+/// Returns the summary of a page body.
+///
+/// This is the content before a `<!-- more -->` marker, or the first paragraph
+/// if there's no marker. Headings preceding the content are skipped.
+pub(crate) fn markdown_summary(body: &str) -> Option<&str> {
+    let mut depth = 0_usize;
+    let mut content_start = None;
+    let mut first_paragraph = None;
+    for (event, range) in Parser::new_ext(body, parser_options()).into_offset_iter() {
+        match event {
+            Event::End(_) => depth -= 1,
+            Event::Start(tag) => {
+                depth += 1;
+                if depth > 1 {
+                    continue;
+                }
+                match tag {
+                    Tag::HtmlBlock if is_more_marker(&body[range.clone()]) => {
+                        let start = content_start.unwrap_or(range.start);
+                        return Some(body[start..range.start].trim()).filter(|s| !s.is_empty());
+                    }
+                    Tag::Heading { .. } if content_start.is_none() => {}
+                    tag => {
+                        content_start.get_or_insert(range.start);
+                        if matches!(tag, Tag::Paragraph) && first_paragraph.is_none() {
+                            first_paragraph = Some(body[range].trim());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    first_paragraph.filter(|s| !s.is_empty())
+}
+
+/// Returns whether an HTML block is a `<!-- more -->` summary marker.
+fn is_more_marker(html: &str) -> bool {
+    html.trim()
+        .strip_prefix("<!--")
+        .and_then(|html| html.strip_suffix("-->"))
+        .is_some_and(|marker| marker.trim().eq_ignore_ascii_case("more"))
+}
+
+/// Parses text keeping only inline events.
+fn inline_events(text: &str) -> impl Iterator<Item = Event<'_>> {
+    Parser::new_ext(text, parser_options()).filter(|event| match event {
+        Event::Start(tag) => matches!(
+            tag,
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link { .. } | Tag::Image { .. }
+        ),
+        Event::End(tag) => matches!(
+            tag,
+            TagEnd::Emphasis
+                | TagEnd::Strong
+                | TagEnd::Strikethrough
+                | TagEnd::Link
+                | TagEnd::Image
+        ),
+        Event::Text(_)
+        | Event::Code(_)
+        | Event::InlineHtml(_)
+        | Event::SoftBreak
+        | Event::HardBreak => true,
+        _ => false,
+    })
+}
+
+/// Escapes markers that would turn the start of an inline text into a block,
+/// such as a heading, a list item or a quote.
+fn escape_block_start(text: &str) -> Cow<'_, str> {
+    /// Returns whether the text after a marker lets the marker start a block.
+    fn is_marker_end(rest: &str) -> bool {
+        rest.is_empty() || rest.starts_with(char::is_whitespace)
+    }
+
+    let text = text.trim();
+    let thematic_break = ['-', '*', '_'].into_iter().any(|marker| {
+        text.chars().filter(|c| *c == marker).count() >= 3
+            && text.chars().all(|c| c == marker || c.is_whitespace())
+    });
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let marker = if thematic_break || text.starts_with(['>', '`', '~']) {
+        Some(0)
+    } else if text.starts_with(['-', '+', '*']) {
+        is_marker_end(&text[1..]).then_some(0)
+    } else if text.starts_with('#') {
+        is_marker_end(text.trim_start_matches('#')).then_some(0)
+    } else if digits > 0 {
+        let rest = &text[digits..];
+        (rest.starts_with(['.', ')']) && is_marker_end(&rest[1..])).then_some(digits)
+    } else {
+        None
+    };
+    match marker {
+        Some(at) => Cow::Owned(format!("{}\\{}", &text[..at], &text[at..])),
+        None => Cow::Borrowed(text),
+    }
+}
+
 /// Returns the content range of a valid ATX heading line, if present.
 fn atx_heading_title(line: &str) -> Option<&str> {
-    let mut events = TextMergeWithOffset::new(Parser::new(line).into_offset_iter());
+    let mut events =
+        TextMergeWithOffset::new(Parser::new_ext(line, parser_options()).into_offset_iter());
     let Some((Event::Start(Tag::Heading { .. }), _)) = events.next() else {
         return None;
     };
@@ -45,19 +332,285 @@ fn atx_heading_title(line: &str) -> Option<&str> {
     None
 }
 
-/// Renders Markdown to HTML using the crate's enabled extensions.
-pub(crate) fn render_markdown(text: &str) -> String {
+/// Returns the parser extensions enabled by the crate.
+pub(crate) fn parser_options() -> Options {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_FOOTNOTES);
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_TASKLISTS);
-    let parser = Parser::new_ext(text, options);
+    options
+}
+
+/// Renders Markdown to HTML using the crate's enabled extensions.
+pub(crate) fn render_markdown(text: &str) -> String {
+    render_markdown_with(text, &RenderOptions::default())
+}
+
+/// Renders Markdown to HTML, applying the given options.
+pub(crate) fn render_markdown_with(text: &str, options: &RenderOptions) -> String {
+    let mut events: Box<dyn Iterator<Item = Event<'_>>> =
+        Box::new(Parser::new_ext(text, options.parser_options()));
+    if options.raw_html == RawHtml::Escape {
+        events = Box::new(events.map(escape_raw_html));
+    }
+    if let Some((base_url, page_url)) = &options.link_base {
+        events = Box::new(events.map(|event| absolutize_link(event, base_url, page_url)));
+    }
+    #[cfg(feature = "highlight")]
+    if let Some(highlighter) = &options.highlighter {
+        // The sanitizer removes `style` attributes, so classes are emitted
+        // instead.
+        #[cfg(feature = "sanitize")]
+        let classed = Highlighter::classed();
+        #[cfg(feature = "sanitize")]
+        let highlighter = if options.raw_html == RawHtml::Sanitize {
+            &classed
+        } else {
+            highlighter
+        };
+        events = Box::new(highlight_code_blocks(events, highlighter).into_iter());
+    }
     let mut html = String::new();
-    html::push_html(&mut html, parser);
+    html::push_html(&mut html, events);
+    #[cfg(feature = "sanitize")]
+    if options.raw_html == RawHtml::Sanitize {
+        html = crate::sanitize::sanitize_html(&html);
+    }
     html
 }
 
+/// Turns raw HTML into text and drops unsafe link destinations.
+fn escape_raw_html(event: Event<'_>) -> Event<'_> {
+    match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_link_destination(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_link_destination(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    }
+}
+
+/// Resolves the destination of a link or an image against a page.
+fn absolutize_link<'a>(event: Event<'a>, base_url: &str, page_url: &str) -> Event<'a> {
+    match event {
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: absolute_destination(dest_url, base_url, page_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: absolute_destination(dest_url, base_url, page_url),
+            title,
+            id,
+        }),
+        event => event,
+    }
+}
+
+/// Resolves a relative destination against a page the way a browser does,
+/// keeping empty destinations and those with a scheme or a host.
+fn absolute_destination<'a>(dest_url: CowStr<'a>, base_url: &str, page_url: &str) -> CowStr<'a> {
+    if dest_url.is_empty() || is_external_url(&dest_url) {
+        return dest_url;
+    }
+    if dest_url.starts_with(['#', '?']) {
+        return format!("{base_url}{page_url}{dest_url}").into();
+    }
+    let split = dest_url.find(['?', '#']).unwrap_or(dest_url.len());
+    let (path, rest) = dest_url.split_at(split);
+    let path = match path.strip_prefix('/') {
+        Some(path) => path.to_string(),
+        None => match page_url.trim_start_matches('/').rsplit_once('/') {
+            Some((dir, _)) => format!("{dir}/{path}"),
+            None => path.to_string(),
+        },
+    };
+    let mut segments = Vec::new();
+    let mut ends_with_dot = false;
+    for segment in path.split('/') {
+        ends_with_dot = matches!(segment, "." | "..");
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." => {}
+            segment => segments.push(segment),
+        }
+    }
+    if ends_with_dot {
+        segments.push("");
+    }
+    format!("{base_url}/{}{rest}", segments.join("/")).into()
+}
+
+/// Returns the destination if it's relative or uses a safe scheme, or an
+/// empty one otherwise.
+fn safe_link_destination(dest_url: CowStr<'_>) -> CowStr<'_> {
+    match url_scheme(&dest_url) {
+        Some(scheme) if !SAFE_LINK_SCHEMES.contains(&scheme.as_str()) => CowStr::Borrowed(""),
+        _ => dest_url,
+    }
+}
+
+/// Replaces fenced code blocks in a known language with highlighted HTML.
+#[cfg(feature = "highlight")]
+fn highlight_code_blocks<'a>(
+    events: impl Iterator<Item = Event<'a>>,
+    highlighter: &Highlighter,
+) -> Vec<Event<'a>> {
+    use pulldown_cmark::CodeBlockKind;
+
+    let mut result = Vec::new();
+    // The fence info string and the events of the code block being collected.
+    let mut block: Option<(CowStr<'a>, Vec<Event<'a>>)> = None;
+    for event in events {
+        match (&mut block, event) {
+            (None, Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))) => {
+                block = Some((info, Vec::new()));
+            }
+            (Some((info, events)), Event::End(TagEnd::CodeBlock)) => {
+                let language = info
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .next()
+                    .unwrap_or_default();
+                let code = events
+                    .iter()
+                    .filter_map(|event| match event {
+                        Event::Text(text) => Some(text.as_ref()),
+                        _ => None,
+                    })
+                    .collect::<String>();
+                match highlighter.highlight(language, &code) {
+                    Some(html) => result.push(Event::Html(html.into())),
+                    None => {
+                        let info = info.clone();
+                        result.push(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))));
+                        result.append(events);
+                        result.push(Event::End(TagEnd::CodeBlock));
+                    }
+                }
+                block = None;
+            }
+            (Some((_, events)), event) => events.push(event),
+            (None, event) => result.push(event),
+        }
+    }
+    result
+}
+
+/// Collects link destinations together with their byte offsets in the text.
+///
+/// Images are skipped, only `[text](target)`-style links and autolinks are
+/// returned.
+pub(crate) fn markdown_links(text: &str) -> Vec<(String, usize)> {
+    Parser::new_ext(text, parser_options())
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::Link { dest_url, .. }) => Some((dest_url.into_string(), range.start)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects the headings of a Markdown text in document order, parsed the
+/// same way and with the same ids as when rendering with the given options.
+pub(crate) fn markdown_headings(text: &str, options: &RenderOptions) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut current: Option<Heading> = None;
+    for event in Parser::new_ext(text, options.parser_options()) {
+        match (&mut current, event) {
+            (None, Event::Start(Tag::Heading { level, id, .. })) => {
+                current = Some(Heading {
+                    level: level as u8,
+                    id: id.map(|id| format!("{}{id}", options.id_prefix())),
+                    text: String::new(),
+                });
+            }
+            (Some(_), Event::End(TagEnd::Heading(_))) => {
+                headings.extend(current.take());
+            }
+            (Some(heading), Event::Text(text) | Event::Code(text)) => {
+                heading.text.push_str(&text);
+            }
+            (Some(heading), Event::SoftBreak | Event::HardBreak) => heading.text.push(' '),
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// Collects fragment identifiers that the rendered HTML would expose.
+///
+/// These are explicit heading ids (`# Foo {#foo}`) and `id` or `name`
+/// attributes of raw HTML tags. Heading ids are collected even though only
+/// [`RenderOptions::heading_attributes`] renders them, as the options used
+/// for serving aren't known here.
+pub(crate) fn markdown_anchors(text: &str) -> HashSet<String> {
+    let mut anchors = HashSet::new();
+    let options = parser_options() | Options::ENABLE_HEADING_ATTRIBUTES;
+    for event in Parser::new_ext(text, options) {
+        match event {
+            Event::Start(Tag::Heading { id: Some(id), .. }) => {
+                anchors.insert(id.into_string());
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                anchors.extend(html_anchor_attributes(&html).map(str::to_string));
+            }
+            _ => {}
+        }
+    }
+    anchors
+}
+
+/// Iterates over quoted `id` and `name` attribute values in an HTML fragment.
+fn html_anchor_attributes(html: &str) -> impl Iterator<Item = &str> {
+    html.char_indices().filter_map(move |(start, c)| {
+        if !c.is_ascii_whitespace() {
+            return None;
+        }
+        let rest = &html[start + 1..];
+        let rest = rest
+            .strip_prefix("id=")
+            .or_else(|| rest.strip_prefix("name="))?;
+        let quote = rest.chars().next().filter(|q| *q == '"' || *q == '\'')?;
+        let value = &rest[1..];
+        let end = value.find(quote)?;
+        Some(&value[..end])
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,6 +627,11 @@ mod tests {
         assert_eq!(title_from_markdown("# *Foo*"), "*Foo*");
         assert_eq!(title_from_markdown("# [Foo](bar)"), "[Foo](bar)");
         assert_eq!(title_from_markdown("# #"), "");
+        assert_eq!(
+            title_from_markdown("# Install {#install}"),
+            "Install {#install}"
+        );
+        assert_eq!(title_from_markdown("# Set {a b}"), "Set {a b}");
         assert_eq!(title_from_markdown("#"), "");
         assert_eq!(title_from_markdown("#5 bolt"), "#5 bolt");
         assert_eq!(title_from_markdown("###Foo"), "###Foo");
@@ -82,6 +640,67 @@ mod tests {
         assert_eq!(title_from_markdown(""), "");
     }
 
+    #[test]
+    fn test_inline_markdown() {
+        let title = "*Foo* [bar](x) &amp; `baz` <b>qux</b>";
+        assert_eq!(inline_markdown_to_text(title), "Foo bar & baz qux");
+        assert_eq!(
+            inline_markdown_to_html(title),
+            "<em>Foo</em> <a href=\"x\">bar</a> &amp; <code>baz</code> <b>qux</b>"
+        );
+
+        for (title, text) in [
+            ("# Foo", "# Foo"),
+            ("- Foo", "- Foo"),
+            ("> Foo", "> Foo"),
+            ("2024. Foo", "2024. Foo"),
+            ("1) Foo", "1) Foo"),
+            ("    Foo", "Foo"),
+            ("Foo\nBar", "Foo Bar"),
+            ("1.5 Foo", "1.5 Foo"),
+            ("---", "---"),
+            ("* * *", "* * *"),
+            ("```", "```"),
+            ("#5 bolt", "#5 bolt"),
+        ] {
+            assert_eq!(inline_markdown_to_text(title), text, "{title:?}");
+        }
+        assert_eq!(inline_markdown_to_html("# a < b"), "# a &lt; b");
+    }
+
+    #[test]
+    fn test_markdown_to_text() {
+        assert_eq!(
+            markdown_to_text(
+                "# Foo &amp; *bar*\n\nSome `code`\nand [a link](x).<br>\n\n- one\n- two\n\n```rust\nfn \
+                 main() {}\n```\n\n> quote",
+                true
+            ),
+            "Foo & bar\n\nSome code and a link.\n\none\ntwo\n\nfn main() {}\n\nquote"
+        );
+        assert_eq!(
+            markdown_to_text("Foo\n\n```\ncode\n```\n\n    indented\n\nBar", false),
+            "Foo\n\nBar"
+        );
+        assert_eq!(markdown_to_text("", true), "");
+    }
+
+    #[test]
+    fn test_markdown_summary() {
+        assert_eq!(
+            markdown_summary("# Title\n\nIntro *one*\nline\n\nSecond"),
+            Some("Intro *one*\nline")
+        );
+        assert_eq!(
+            markdown_summary("# Title\n\nIntro\n\n- item\n\n<!-- more -->\n\nRest"),
+            Some("Intro\n\n- item")
+        );
+        assert_eq!(markdown_summary("Intro\n<!--MORE-->\nRest"), Some("Intro"));
+        assert_eq!(markdown_summary("# Title\n\n<!-- more -->\n\nRest"), None);
+        assert_eq!(markdown_summary("- no\n- paragraphs"), None);
+        assert_eq!(markdown_summary(""), None);
+    }
+
     #[test]
     fn test_markdown_enables_extensions() {
         assert!(render_markdown("~~gone~~").contains("<del>gone</del>"));
@@ -92,4 +711,175 @@ mod tests {
         assert!(footnotes.contains("footnote-reference"));
         assert!(footnotes.contains("footnote-definition"));
     }
+
+    #[test]
+    fn test_markdown_heading_attributes() {
+        assert_eq!(render_markdown("# Set {a b}"), "<h1>Set {a b}</h1>\n");
+        assert_eq!(render_markdown("# Foo {#bar}"), "<h1>Foo {#bar}</h1>\n");
+
+        let options = RenderOptions::new().heading_attributes(true);
+        assert_eq!(
+            render_markdown_with("# Foo {#bar}", &options),
+            "<h1 id=\"bar\">Foo</h1>\n"
+        );
+    }
+
+    #[cfg(feature = "highlight")]
+    #[test]
+    fn test_markdown_highlighting() {
+        let options = RenderOptions::new().highlight(Highlighter::classed());
+        let html = render_markdown_with("```rust,ignore\nfn main() {}\n```", &options);
+        assert!(html.starts_with("<pre class=\"hl-code\"><code class=\"language-rust\">"));
+        assert!(html.contains("<span class=\"hl-storage"));
+
+        assert_eq!(
+            render_markdown_with("```\nfn main() {}\n```\n\n```nope\nfoo\n```", &options),
+            render_markdown("```\nfn main() {}\n```\n\n```nope\nfoo\n```")
+        );
+    }
+
+    #[test]
+    fn test_markdown_escapes_raw_html() {
+        let options = RenderOptions::new().raw_html(RawHtml::Escape);
+        assert_eq!(
+            render_markdown_with("<script>alert(1)</script>\n\nHi <b>there</b>", &options),
+            "&lt;script&gt;alert(1)&lt;/script&gt;\n<p>Hi &lt;b&gt;there&lt;/b&gt;</p>\n"
+        );
+        assert_eq!(
+            render_markdown_with(
+                "[a](javascript:alert(1)) [b](/foo:bar) [c](https://x.org) [d](Mailto:a@b.c)",
+                &options
+            ),
+            "<p><a href=\"\">a</a> <a href=\"/foo:bar\">b</a> <a \
+             href=\"https://x.org\">c</a> <a href=\"Mailto:a@b.c\">d</a></p>\n"
+        );
+        assert_eq!(
+            render_markdown_with("[a](<java\tscript:alert(1)>)", &options),
+            "<p><a href=\"\">a</a></p>\n"
+        );
+    }
+
+    #[cfg(feature = "sanitize")]
+    #[test]
+    fn test_markdown_sanitizes_raw_html() {
+        let options = RenderOptions::new()
+            .raw_html(RawHtml::Sanitize)
+            .heading_attributes(true);
+        assert_eq!(
+            render_markdown_with(
+                "# Foo {#foo}\n\n<img src=x onerror=alert(1)>\n\n[a](javascript:alert(1))",
+                &options
+            ),
+            "<h1 id=\"user-content-foo\">Foo</h1>\n<img src=\"x\">\n<p><a rel=\"noopener \
+             noreferrer\">a</a></p>\n"
+        );
+        assert_eq!(
+            render_markdown_with("- [x] done\n\n| a |\n|:-:|\n| b |", &options),
+            render_markdown("- [x] done\n\n| a |\n|:-:|\n| b |")
+                .replace("/>", ">")
+                .replace(
+                    "type=\"checkbox\" checked=\"\"",
+                    "checked=\"\" type=\"checkbox\""
+                )
+        );
+        let footnotes = render_markdown_with("[^1]\n\n[^1]: Note", &options);
+        assert!(footnotes.contains("<a href=\"#user-content-1\""));
+        assert!(footnotes.contains("<div class=\"footnote-definition\" id=\"user-content-1\">"));
+    }
+
+    #[cfg(all(feature = "sanitize", feature = "highlight"))]
+    #[test]
+    fn test_markdown_sanitize_highlights_with_classes() {
+        let options = RenderOptions::new()
+            .raw_html(RawHtml::Sanitize)
+            .highlight(Highlighter::inline("base16-ocean.dark").unwrap());
+        let html = render_markdown_with("```rust\nfn main() {}\n```", &options);
+        assert!(html.starts_with("<pre class=\"hl-code\">"), "{html}");
+        assert!(html.contains("class=\"hl-"), "{html}");
+        assert!(!html.contains("style="), "{html}");
+    }
+
+    #[test]
+    fn test_absolute_destination() {
+        let resolve = |dest| {
+            absolute_destination(CowStr::Borrowed(dest), "https://x.org", "/blog/first")
+                .into_string()
+        };
+        assert_eq!(resolve("second"), "https://x.org/blog/second");
+        assert_eq!(resolve("./img/a.png"), "https://x.org/blog/img/a.png");
+        assert_eq!(resolve("../about?x=1#y"), "https://x.org/about?x=1#y");
+        assert_eq!(resolve("../.."), "https://x.org/");
+        assert_eq!(resolve("/guides/"), "https://x.org/guides/");
+        assert_eq!(resolve("#intro"), "https://x.org/blog/first#intro");
+        assert_eq!(resolve("https://y.org/a"), "https://y.org/a");
+        assert_eq!(resolve("//y.org/a"), "//y.org/a");
+        assert_eq!(resolve("mailto:a@b.c"), "mailto:a@b.c");
+        assert_eq!(resolve(""), "");
+        assert_eq!(
+            absolute_destination(CowStr::Borrowed("a.png"), "https://x.org", "/").into_string(),
+            "https://x.org/a.png"
+        );
+    }
+
+    #[test]
+    fn test_markdown_links() {
+        assert_eq!(
+            markdown_links("[a](/foo)\n\n![img](/logo.png) <https://example.com>"),
+            vec![
+                ("/foo".to_string(), 0),
+                ("https://example.com".to_string(), 29),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_headings() {
+        let text =
+            "# *Install* `flatpage` {#install}\n\nText\n\n## Linux\n\n```\n# not a heading\n```";
+        assert_eq!(
+            markdown_headings(text, &RenderOptions::new())[0],
+            Heading {
+                level: 1,
+                id: None,
+                text: "Install flatpage {#install}".into(),
+            }
+        );
+        let headings = markdown_headings(text, &RenderOptions::new().heading_attributes(true));
+        assert_eq!(
+            headings,
+            vec![
+                Heading {
+                    level: 1,
+                    id: Some("install".into()),
+                    text: "Install flatpage".into(),
+                },
+                Heading {
+                    level: 2,
+                    id: None,
+                    text: "Linux".into(),
+                },
+            ]
+        );
+
+        #[cfg(feature = "sanitize")]
+        {
+            let options = RenderOptions::new()
+                .heading_attributes(true)
+                .raw_html(RawHtml::Sanitize);
+            assert_eq!(
+                markdown_headings(text, &options)[0].id.as_deref(),
+                Some("user-content-install")
+            );
+        }
+    }
+
+    #[test]
+    fn test_markdown_anchors() {
+        let anchors = markdown_anchors(
+            "# Foo {#foo}\n\n## Bar\n\n<div id=\"baz\"></div>\n\n<a name='qux'></a> text",
+        );
+        let mut anchors = anchors.into_iter().collect::<Vec<_>>();
+        anchors.sort();
+        assert_eq!(anchors, vec!["baz", "foo", "qux"]);
+    }
 }
@@ -5,10 +5,15 @@ use std::{fs, io, path::Path};
 use serde::de::DeserializeOwned;
 
 use crate::{
-    Error, Result,
-    markdown::{render_markdown, title_from_markdown},
-    util::AbsPagePath,
+    CacheValidators, Error, Heading, PageMapping, PageStats, RenderOptions, Result,
+    markdown::{
+        inline_markdown_to_html, inline_markdown_to_text, markdown_headings, markdown_summary,
+        markdown_to_text, render_markdown, render_markdown_with, title_from_markdown,
+    },
+    util::{AbsPagePath, NormalizedUrl},
 };
+#[cfg(doc)]
+use crate::{FlatPageStore, SegmentPolicy};
 
 /// Parsed frontmatter fields before they are assembled into a [`FlatPage`].
 #[derive(Debug, serde::Deserialize)]
@@ -23,7 +28,11 @@ struct Frontmatter<Extra = ()> {
 }
 
 /// Flat page
-#[derive(Debug)]
+///
+/// Fields are added as more page data is read, so the struct can't be built
+/// or exhaustively destructured outside the crate.
+#[derive(Debug, serde::Serialize)]
+#[non_exhaustive]
 pub struct FlatPage<Extra = ()> {
     /// Page title
     pub title: String,
@@ -33,6 +42,33 @@ pub struct FlatPage<Extra = ()> {
     pub body: String,
     /// Extra frontmatter fields (except `title` and `description`)
     pub extra: Extra,
+    /// Content hash and file modification time for HTTP caching
+    #[serde(skip)]
+    pub validators: CacheValidators,
+}
+
+/// Page summary, see [`FlatPage::summary`]
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize)]
+pub struct Summary {
+    /// Raw markdown version of the summary
+    pub markdown: String,
+}
+
+impl Summary {
+    /// [`Summary::markdown`] rendered to HTML
+    pub fn html(&self) -> String {
+        render_markdown(&self.markdown)
+    }
+
+    /// [`Summary::markdown`] rendered to HTML with the given options
+    pub fn html_with(&self, options: &RenderOptions) -> String {
+        render_markdown_with(&self.markdown, options)
+    }
+
+    /// [`Summary::markdown`] as plain text, without code blocks
+    pub fn text(&self) -> String {
+        markdown_to_text(&self.markdown, false)
+    }
 }
 
 impl<Extra: DeserializeOwned> FlatPage<Extra> {
@@ -43,11 +79,34 @@ impl<Extra: DeserializeOwned> FlatPage<Extra> {
     ///
     /// Returns `Ok(None)` for invalid URLs and missing pages. Returns `Err` for
     /// I/O failures and frontmatter parsing errors.
-    pub fn by_url(root: impl AsRef<Path>, url: &str) -> Result<Option<Self>> {
-        let Some(path) = AbsPagePath::from_raw_url(root.as_ref(), url) else {
+    pub fn by_url(root: impl AsRef<Path>, url: impl AsRef<str>) -> Result<Option<Self>> {
+        Self::by_url_with(root, url, &PageMapping::default())
+    }
+
+    /// Returns a page by its URL, mapped to files with the segment policy,
+    /// index names and extensions of a mapping, see [`FlatPage::by_url`].
+    ///
+    /// Candidate files are tried in the mapping's order of preference, so
+    /// `/guides/` returns `guides/index.md` rather than `guides/README.md`
+    /// when both exist.
+    ///
+    /// The file is looked up by the normalized name, so with
+    /// [`SegmentPolicy::Unicode`] file names should be in NFC unless the
+    /// filesystem normalizes them. [`FlatPageStore`] has no such limitation.
+    pub fn by_url_with(
+        root: impl AsRef<Path>,
+        url: impl AsRef<str>,
+        mapping: &PageMapping,
+    ) -> Result<Option<Self>> {
+        let Ok(url) = NormalizedUrl::parse(url.as_ref(), &mapping.segment_policy) else {
             return Ok(None);
         };
-        Self::by_path(&path)
+        for path in AbsPagePath::candidates(root.as_ref(), &url, mapping) {
+            if let Some(page) = Self::by_path(path)? {
+                return Ok(Some(page));
+            }
+        }
+        Ok(None)
     }
 
     /// Returns a page by its file path.
@@ -55,14 +114,88 @@ impl<Extra: DeserializeOwned> FlatPage<Extra> {
     /// Returns `Ok(None)` when the file does not exist.
     pub fn by_path(path: impl AsRef<Path>) -> Result<Option<Self>> {
         let path = path.as_ref();
-        let content = match fs::read_to_string(path) {
-            Ok(c) => c,
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
             Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
             Err(e) => return Err(Error::read_file(e, path)),
         };
-        Self::from_content(&content)
-            .map(Some)
-            .map_err(|e| Error::parse_frontmatter(e, path))
+        let modified = file
+            .metadata()
+            .map_err(|e| Error::read_metadata(e, path))?
+            .modified()
+            .ok();
+        let content = io::read_to_string(file).map_err(|e| Error::read_file(e, path))?;
+        let mut page =
+            Self::from_content(&content).map_err(|e| Error::parse_frontmatter(e, path))?;
+        page.validators.modified = modified;
+        Ok(Some(page))
+    }
+
+    /// [`FlatPage::title`] with inline Markdown stripped and entities decoded
+    ///
+    /// Use it for `<title>` tags and other plain-text contexts.
+    pub fn title_text(&self) -> String {
+        inline_markdown_to_text(&self.title)
+    }
+
+    /// [`FlatPage::title`] with inline Markdown rendered to HTML
+    pub fn title_html(&self) -> String {
+        inline_markdown_to_html(&self.title)
+    }
+
+    /// Returns the content before a `<!-- more -->` marker or, if there's no
+    /// marker, the first paragraph.
+    ///
+    /// Headings preceding the content, such as the title heading, are
+    /// skipped. Returns `None` if the summary is empty.
+    pub fn summary(&self) -> Option<Summary> {
+        markdown_summary(&self.body).map(|markdown| Summary {
+            markdown: markdown.to_string(),
+        })
+    }
+
+    /// [`FlatPage::description`] or, if it's missing, the plain-text
+    /// [`FlatPage::summary`]
+    pub fn meta_description(&self) -> Option<String> {
+        self.description
+            .clone()
+            .or_else(|| self.summary().map(|summary| summary.text()))
+    }
+
+    /// Word count, heading count and other statistics of [`FlatPage::body`]
+    ///
+    /// Use [`PageStats::reading_time`] for a reading time estimate.
+    pub fn stats(&self) -> PageStats {
+        PageStats::from_markdown(&self.body)
+    }
+
+    /// Headings of [`FlatPage::body`] in document order, for a table of
+    /// contents
+    ///
+    /// Trailing `{#id}` blocks are kept as text, use
+    /// [`FlatPage::headings_with`] to parse them.
+    pub fn headings(&self) -> Vec<Heading> {
+        self.headings_with(&RenderOptions::default())
+    }
+
+    /// Headings of [`FlatPage::body`] as rendered by
+    /// [`FlatPage::html_with`] with the given options
+    pub fn headings_with(&self, options: &RenderOptions) -> Vec<Heading> {
+        markdown_headings(&self.body, options)
+    }
+
+    /// [`FlatPage::body`] as plain text
+    ///
+    /// Markdown syntax and HTML tags are removed, entities are decoded and
+    /// paragraphs are separated by blank lines. HTML blocks are dropped
+    /// entirely.
+    pub fn plain_text(&self) -> String {
+        markdown_to_text(&self.body, true)
+    }
+
+    /// [`FlatPage::plain_text`] without code blocks
+    pub fn plain_text_without_code(&self) -> String {
+        markdown_to_text(&self.body, false)
     }
 
     /// [`FlatPage::body`] rendered to HTML
@@ -70,6 +203,11 @@ impl<Extra: DeserializeOwned> FlatPage<Extra> {
         render_markdown(&self.body)
     }
 
+    /// [`FlatPage::body`] rendered to HTML with the given options
+    pub fn html_with(&self, options: &RenderOptions) -> String {
+        render_markdown_with(&self.body, options)
+    }
+
     /// Parses a page from text
     fn from_content(content: &str) -> std::result::Result<Self, markdown_frontmatter::Error> {
         let (
@@ -85,6 +223,7 @@ impl<Extra: DeserializeOwned> FlatPage<Extra> {
             description,
             body: body.to_string(),
             extra,
+            validators: CacheValidators::new(content, None),
         })
     }
 }
@@ -93,7 +232,7 @@ impl<Extra: DeserializeOwned> FlatPage<Extra> {
 mod tests {
     use super::*;
     use crate::{
-        Error,
+        Error, SegmentPolicy,
         test_helpers::{TestDir, write_page},
     };
 
@@ -122,6 +261,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flatpage_title_text_and_html() {
+        let page = FlatPage::<()>::from_content("# *Foo* [bar](x) &amp; baz").unwrap();
+        assert_eq!(page.title, "*Foo* [bar](x) &amp; baz");
+        assert_eq!(page.title_text(), "Foo bar & baz");
+        assert_eq!(
+            page.title_html(),
+            "<em>Foo</em> <a href=\"x\">bar</a> &amp; baz"
+        );
+
+        #[cfg(feature = "yaml")]
+        {
+            let page = FlatPage::<()>::from_content("---\ntitle: \"**Bar**\"\n---\n# Foo").unwrap();
+            assert_eq!(page.title_text(), "Bar");
+            assert_eq!(page.title_html(), "<strong>Bar</strong>");
+        }
+    }
+
+    #[test]
+    fn flatpage_summary() {
+        let page =
+            FlatPage::<()>::from_content("# Foo\n\nIntro *text*\n\n<!-- more -->\n\nRest").unwrap();
+        let summary = page.summary().unwrap();
+        assert_eq!(summary.markdown, "Intro *text*");
+        assert_eq!(summary.html(), "<p>Intro <em>text</em></p>\n");
+        assert_eq!(summary.text(), "Intro text");
+        assert_eq!(page.meta_description().as_deref(), Some("Intro text"));
+
+        let page = FlatPage::<()>::from_content("# Foo\n\nFirst\n\nSecond").unwrap();
+        assert_eq!(page.summary().unwrap().markdown, "First");
+
+        assert!(
+            FlatPage::<()>::from_content("# Foo")
+                .unwrap()
+                .summary()
+                .is_none()
+        );
+
+        #[cfg(feature = "yaml")]
+        assert_eq!(
+            FlatPage::<()>::from_content("---\ndescription: Bar\n---\n# Foo\n\nFirst")
+                .unwrap()
+                .meta_description()
+                .as_deref(),
+            Some("Bar")
+        );
+    }
+
+    #[test]
+    fn flatpage_stats() {
+        let page =
+            FlatPage::<()>::from_content("# Foo\n\nOne two three\n\n```\nskip me\n```").unwrap();
+        assert_eq!(
+            page.stats(),
+            PageStats {
+                word_count: 4,
+                heading_count: 1,
+                code_block_count: 1,
+                image_count: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn flatpage_plain_text() {
+        let page = FlatPage::<()>::from_content(
+            "# Foo\n\nHello <b>*bold*</b>\nworld &amp; more\n\n```sh\nls -la\n```\n\n<div>\nhidden\n</div>\n\nBye",
+        )
+        .unwrap();
+        assert_eq!(
+            page.plain_text(),
+            "Foo\n\nHello bold world & more\n\nls -la\n\nBye"
+        );
+        assert_eq!(
+            page.plain_text_without_code(),
+            "Foo\n\nHello bold world & more\n\nBye"
+        );
+    }
+
     #[test]
     fn flatpage_description() {
         assert_eq!(FlatPage::<()>::from_content("").unwrap().description, None);
@@ -207,6 +425,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flatpage_by_url_with_segment_policy() {
+        let root = TestDir::new();
+        write_page(root.path(), "über-uns.md", "# Über uns");
+
+        assert!(
+            FlatPage::<()>::by_url(root.path(), "/über-uns")
+                .unwrap()
+                .is_none()
+        );
+        let mapping = PageMapping::new().segment_policy(SegmentPolicy::Unicode);
+        let page = FlatPage::<()>::by_url_with(root.path(), "/u\u{308}ber-uns", &mapping)
+            .unwrap()
+            .unwrap();
+        assert_eq!(page.title, "Über uns");
+    }
+
+    #[test]
+    fn flatpage_by_url_with_page_mapping() {
+        let root = TestDir::new();
+        write_page(root.path(), "guides/README.md", "# Guides readme");
+        write_page(root.path(), "guides/install.markdown", "# Install");
+        let mapping = PageMapping::new()
+            .index_names(["index", "README"])
+            .extensions(["md", "markdown"]);
+
+        let title = |url| {
+            FlatPage::<()>::by_url_with(root.path(), url, &mapping)
+                .unwrap()
+                .map(|page| page.title)
+        };
+        assert_eq!(title("/guides/").as_deref(), Some("Guides readme"));
+        assert_eq!(title("/guides/install").as_deref(), Some("Install"));
+        assert_eq!(title("/guides/README"), None);
+        assert!(
+            FlatPage::<()>::by_url(root.path(), "/guides/")
+                .unwrap()
+                .is_none()
+        );
+
+        write_page(root.path(), "guides/index.md", "# Guides index");
+        assert_eq!(title("/guides/").as_deref(), Some("Guides index"));
+    }
+
     #[test]
     fn flatpage_by_path_returns_none_for_missing_file() {
         let root = TestDir::new();
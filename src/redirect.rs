@@ -0,0 +1,314 @@
+//! Redirects declared in page frontmatter or in a `_redirects` file.
+
+use std::{fmt, path::PathBuf};
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{self, SeqAccess, Visitor, value::SeqAccessDeserializer},
+};
+
+use crate::{
+    Lookup, PageUrl, SegmentPolicy,
+    error::{RuleProblem, UrlProblem},
+    util::{NormalizedUrl, is_external_url},
+};
+
+/// Name of the redirect rules file in the pages root.
+pub(crate) const REDIRECTS_FILE: &str = "_redirects";
+/// Status of rules without one.
+const DEFAULT_STATUS: u16 = 301;
+/// Supported rule statuses.
+const REDIRECT_STATUSES: [u16; 5] = [301, 302, 303, 307, 308];
+
+/// A redirect pointing at a missing page, see
+/// [`FlatPageStore::check_redirects`]
+///
+/// [`FlatPageStore::check_redirects`]: crate::FlatPageStore::check_redirects
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BrokenRedirect {
+    /// Source URL or pattern of the redirect
+    pub from: String,
+    /// The missing target
+    pub to: String,
+    /// Page file or `_redirects` file declaring the redirect
+    pub path: PathBuf,
+    /// 1-based line number in the `_redirects` file, `None` for frontmatter
+    /// redirects
+    pub line: Option<usize>,
+}
+
+/// Frontmatter fields declaring redirects.
+#[derive(Debug, Deserialize)]
+pub(crate) struct RedirectFrontmatter {
+    /// Old URLs redirecting to the page, also accepted as `redirect_from`.
+    #[serde(default, alias = "redirect_from", deserialize_with = "one_or_many")]
+    pub(crate) aliases: Vec<String>,
+    /// URL the page redirects to, absolute, relative to the page or a URL of
+    /// another site.
+    pub(crate) redirect_to: Option<String>,
+}
+
+/// A redirect indexed by the store.
+#[derive(Debug)]
+pub(crate) struct Redirect {
+    /// URL to redirect to.
+    pub(crate) target: RedirectTarget,
+    /// Page file declaring the redirect.
+    pub(crate) path: PathBuf,
+}
+
+impl Redirect {
+    /// Returns the lookup result for the source URL of the redirect.
+    pub(crate) fn lookup(&self) -> Lookup<'static> {
+        match &self.target {
+            RedirectTarget::Page(url) => Lookup::Redirect(url.clone()),
+            RedirectTarget::External(url) => Lookup::Rule {
+                location: url.clone(),
+                status: DEFAULT_STATUS,
+            },
+        }
+    }
+}
+
+/// Target of a frontmatter redirect.
+#[derive(Clone, Debug)]
+pub(crate) enum RedirectTarget {
+    /// A page of the site.
+    Page(PageUrl),
+    /// A URL of another site, given in `redirect_to`.
+    External(String),
+}
+
+impl RedirectTarget {
+    /// Returns the target URL.
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::Page(url) => url.as_str(),
+            Self::External(url) => url,
+        }
+    }
+}
+
+/// A rule of the `_redirects` file.
+#[derive(Debug)]
+pub(crate) struct RedirectRule {
+    /// Source URL, or the folder URL of a `/folder/*` pattern.
+    pub(crate) from: String,
+    /// Whether the source ends with `*`, matching everything under `from`.
+    pub(crate) splat: bool,
+    /// Destination URL, possibly containing `:splat`.
+    pub(crate) to: String,
+    /// HTTP redirect status.
+    pub(crate) status: u16,
+    /// 1-based line number.
+    pub(crate) line: usize,
+}
+
+impl RedirectRule {
+    /// Returns the destination if the rule matches a normalized URL.
+    pub(crate) fn apply(&self, url: &str) -> Option<String> {
+        if !self.splat {
+            return (url == self.from).then(|| self.to.clone());
+        }
+        let splat = url.strip_prefix(&self.from)?;
+        Some(self.to.replace(":splat", splat))
+    }
+
+    /// Returns whether the destination is a URL of another site.
+    pub(crate) fn is_external(&self) -> bool {
+        is_external_url(&self.to)
+    }
+}
+
+/// Parses a Netlify-style `_redirects` file with `source destination
+/// [status]` lines.
+///
+/// Blank lines and comments are skipped. A comment starts with a `#` field,
+/// either at the start of the line or after the rule, so destinations can
+/// have fragments. Returns the 1-based line number
+/// with the problem of the first invalid rule.
+pub(crate) fn parse_redirects(
+    content: &str,
+    policy: &SegmentPolicy,
+) -> Result<Vec<RedirectRule>, (usize, RuleProblem)> {
+    let mut rules = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line_number = i + 1;
+        let mut fields = line
+            .split_whitespace()
+            .take_while(|field| !field.starts_with('#'));
+        let Some(source) = fields.next() else {
+            continue;
+        };
+        let rule = parse_rule(line_number, source, fields, policy)
+            .map_err(|problem| (line_number, problem))?;
+        rules.push(rule);
+    }
+    Ok(rules)
+}
+
+/// Parses the fields of a single rule.
+fn parse_rule<'a>(
+    line: usize,
+    source: &str,
+    mut fields: impl Iterator<Item = &'a str>,
+    policy: &SegmentPolicy,
+) -> Result<RedirectRule, RuleProblem> {
+    let (from, splat) = match source.strip_suffix('*') {
+        Some(folder) => (folder, true),
+        None => (source, false),
+    };
+    let from = NormalizedUrl::parse(from, policy).map_err(RuleProblem::InvalidSource)?;
+    if splat && !from.as_ref().ends_with('/') {
+        return Err(RuleProblem::InvalidSource(UrlProblem::InvalidSegment(
+            source.rsplit('/').next().unwrap_or_default().to_string(),
+        )));
+    }
+
+    let to = fields.next().ok_or(RuleProblem::MissingDestination)?;
+    if !is_external_url(to) {
+        let path = to.split(['?', '#']).next().unwrap_or_default();
+        if !path.contains(":splat") {
+            NormalizedUrl::parse(path, policy).map_err(RuleProblem::InvalidDestination)?;
+        } else if !path.starts_with('/') {
+            return Err(RuleProblem::InvalidDestination(
+                UrlProblem::MissingLeadingSlash,
+            ));
+        }
+    }
+
+    let status = match fields.next() {
+        Some(status) => status
+            .parse()
+            .ok()
+            .filter(|status| REDIRECT_STATUSES.contains(status))
+            .ok_or_else(|| RuleProblem::UnsupportedStatus(status.to_string()))?,
+        None => DEFAULT_STATUS,
+    };
+    if fields.next().is_some() {
+        return Err(RuleProblem::UnexpectedField);
+    }
+    Ok(RedirectRule {
+        from: from.as_ref().to_string(),
+        splat,
+        to: to.to_string(),
+        status,
+        line,
+    })
+}
+
+/// Deserializes either a single string or a list of strings.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    /// Visitor accepting a string or a sequence of strings.
+    struct OneOrMany;
+
+    impl<'de> Visitor<'de> for OneOrMany {
+        type Value = Vec<String>;
+
+        /// Describes the expected value for error messages.
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a URL or a list of URLs")
+        }
+
+        /// Wraps a single URL into a list.
+        fn visit_str<E: de::Error>(self, url: &str) -> Result<Self::Value, E> {
+            Ok(vec![url.to_string()])
+        }
+
+        /// Collects a list of URLs.
+        fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            Vec::deserialize(SeqAccessDeserializer::new(seq))
+        }
+    }
+
+    deserializer.deserialize_any(OneOrMany)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_redirects() {
+        let policy = SegmentPolicy::Ascii;
+        let rules = parse_redirects(
+            "# Moved\n\n/old /new\n/blog/*  /news/:splat  302 # temporary\n/ext https://example.com/",
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(rules.len(), 3);
+        assert_eq!((rules[0].line, rules[0].status), (3, 301));
+        assert_eq!(rules[0].apply("/old").as_deref(), Some("/new"));
+        assert_eq!(rules[0].apply("/old/"), None);
+        assert_eq!((rules[1].line, rules[1].status), (4, 302));
+        assert_eq!(
+            rules[1].apply("/blog/2024/hello").as_deref(),
+            Some("/news/2024/hello")
+        );
+        assert_eq!(rules[1].apply("/blog/").as_deref(), Some("/news/"));
+        assert_eq!(rules[1].apply("/blog"), None);
+        assert!(rules[2].is_external());
+
+        let rules =
+            parse_redirects("  # /old /new\n/setup /guides/install#linux 302", &policy).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!((rules[0].line, rules[0].status), (2, 302));
+        assert_eq!(
+            rules[0].apply("/setup").as_deref(),
+            Some("/guides/install#linux")
+        );
+
+        let problem = |content| parse_redirects(content, &policy).unwrap_err();
+        assert_eq!(problem("/a /b\n/old"), (2, RuleProblem::MissingDestination));
+        assert_eq!(
+            problem("old /new"),
+            (
+                1,
+                RuleProblem::InvalidSource(UrlProblem::MissingLeadingSlash)
+            )
+        );
+        assert_eq!(
+            problem("/blog* /news"),
+            (
+                1,
+                RuleProblem::InvalidSource(UrlProblem::InvalidSegment("blog*".into()))
+            )
+        );
+        assert_eq!(
+            problem("/old new"),
+            (
+                1,
+                RuleProblem::InvalidDestination(UrlProblem::MissingLeadingSlash)
+            )
+        );
+        assert_eq!(
+            problem("/old /new 200"),
+            (1, RuleProblem::UnsupportedStatus("200".into()))
+        );
+        assert_eq!(
+            problem("/old /new 301 x"),
+            (1, RuleProblem::UnexpectedField)
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_redirect_frontmatter() {
+        let parse = |content| {
+            markdown_frontmatter::parse::<RedirectFrontmatter>(content)
+                .unwrap()
+                .0
+        };
+        assert_eq!(
+            parse("---\naliases: [/old, /older]\n---\n").aliases,
+            vec!["/old", "/older"]
+        );
+        assert_eq!(
+            parse("---\nredirect_from: /old\n---\n").aliases,
+            vec!["/old"]
+        );
+        let redirect = parse("---\nredirect_to: ../new\n---\n");
+        assert!(redirect.aliases.is_empty());
+        assert_eq!(redirect.redirect_to.as_deref(), Some("../new"));
+    }
+}
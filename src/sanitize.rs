@@ -0,0 +1,132 @@
+//! Allowlist-based sanitizing of rendered HTML.
+
+use std::sync::OnceLock;
+
+use ammonia::Builder;
+
+/// Removes scripts, event-handler attributes, unsafe URLs and other markup
+/// outside the allowlist from rendered HTML.
+///
+/// On top of the `ammonia` defaults, the allowlist keeps what the renderer
+/// itself emits: heading and footnote ids, footnote and highlighting classes,
+/// task-list checkboxes and table alignment. Ids get the [`ID_PREFIX`], with
+/// `#fragment` links rewritten to match, so they can't clobber DOM globals
+/// such as `window.location`. Other classes, `style` attributes and input
+/// types are removed, so content can't overlay the page with the site's own
+/// styles or ask for passwords.
+pub(crate) fn sanitize_html(html: &str) -> String {
+    static SANITIZER: OnceLock<Builder<'static>> = OnceLock::new();
+    SANITIZER
+        .get_or_init(|| {
+            let mut builder = Builder::default();
+            builder
+                .add_generic_attributes(["class"])
+                .add_tags(["input"])
+                .add_tag_attributes("input", ["checked", "disabled"])
+                .set_tag_attribute_value("input", "type", "checkbox")
+                .add_tag_attributes("td", ["style"])
+                .add_tag_attributes("th", ["style"])
+                .attribute_filter(|element, attribute, value| match attribute {
+                    "id" => ID_TAGS
+                        .contains(&element)
+                        .then(|| format!("{ID_PREFIX}{value}").into()),
+                    "href" => match value.strip_prefix('#') {
+                        Some(fragment) => Some(format!("#{ID_PREFIX}{fragment}").into()),
+                        None => Some(value.into()),
+                    },
+                    "class" => renderer_classes(value).map(Into::into),
+                    "style" => ALIGNMENT_STYLES.contains(&value).then_some(value.into()),
+                    _ => Some(value.into()),
+                });
+            for tag in ID_TAGS {
+                builder.add_tag_attributes(tag, ["id"]);
+            }
+            builder
+        })
+        .clean(html)
+        .to_string()
+}
+
+/// Prefix of ids in sanitized HTML.
+pub(crate) const ID_PREFIX: &str = "user-content-";
+
+/// Tags the renderer gives ids: headings and footnote definitions.
+const ID_TAGS: [&str; 7] = ["h1", "h2", "h3", "h4", "h5", "h6", "div"];
+
+/// Classes of footnotes emitted by the renderer.
+const FOOTNOTE_CLASSES: [&str; 3] = [
+    "footnote-definition",
+    "footnote-definition-label",
+    "footnote-reference",
+];
+
+/// Table cell styles emitted by the renderer for column alignment.
+const ALIGNMENT_STYLES: [&str; 3] = [
+    "text-align: left",
+    "text-align: center",
+    "text-align: right",
+];
+
+/// Keeps the classes of a `class` attribute that the renderer emits for
+/// footnotes, code block languages and highlighting.
+fn renderer_classes(value: &str) -> Option<String> {
+    let classes = value
+        .split_ascii_whitespace()
+        .filter(|class| {
+            FOOTNOTE_CLASSES.contains(class)
+                || class.starts_with("language-")
+                || class.starts_with("hl-")
+        })
+        .collect::<Vec<_>>();
+    (!classes.is_empty()).then(|| classes.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize_html;
+
+    #[test]
+    fn test_sanitize_html() {
+        assert_eq!(
+            sanitize_html("<p onclick=\"x()\">Hi<script>alert(1)</script></p>"),
+            "<p>Hi</p>"
+        );
+        assert_eq!(
+            sanitize_html("<a href=\"javascript:alert(1)\">x</a>"),
+            "<a rel=\"noopener noreferrer\">x</a>"
+        );
+        assert_eq!(
+            sanitize_html(
+                "<h2 id=\"foo\">Foo</h2><a href=\"#foo\">x</a><input disabled=\"\" \
+                 type=\"checkbox\">"
+            ),
+            "<h2 id=\"user-content-foo\">Foo</h2><a href=\"#user-content-foo\" rel=\"noopener \
+             noreferrer\">x</a><input disabled=\"\" type=\"checkbox\">"
+        );
+        assert_eq!(
+            sanitize_html("<div id=\"location\">a</div><h1 id=\"cookie\">b</h1>"),
+            "<div id=\"user-content-location\">a</div><h1 id=\"user-content-cookie\">b</h1>"
+        );
+        assert_eq!(
+            sanitize_html("<input type=\"password\" name=\"p\"><input>"),
+            "<input type=\"checkbox\"><input type=\"checkbox\">"
+        );
+        assert_eq!(
+            sanitize_html(
+                "<div class=\"modal fixed footnote-definition\">a</div><code \
+                 class=\"language-rust hl-x\">b</code><p class=\"overlay\">c</p>"
+            ),
+            "<div class=\"footnote-definition\">a</div><code class=\"language-rust \
+             hl-x\">b</code><p>c</p>"
+        );
+        assert_eq!(
+            sanitize_html(
+                "<span id=\"x\" style=\"position:fixed;inset:0\">a</span><table><tr>\
+                 <td style=\"text-align: right\">b</td><th style=\"color: red\">c</th>\
+                 </tr></table>"
+            ),
+            "<span>a</span><table><tbody><tr><td style=\"text-align: right\">b</td><th>c</th>\
+             </tr></tbody></table>"
+        );
+    }
+}
@@ -0,0 +1,321 @@
+//! In-memory full-text search over page titles, descriptions and bodies.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{Tokenizer, tokenizer::words, util::escape_html};
+
+/// BM25 term frequency saturation.
+const K1: f64 = 1.2;
+/// BM25 document length normalization.
+const B: f64 = 0.75;
+/// Weight of title terms relative to body terms.
+const TITLE_WEIGHT: f64 = 3.0;
+/// Weight of description terms relative to body terms.
+const DESCRIPTION_WEIGHT: f64 = 2.0;
+/// Number of words shown before the first match in a snippet.
+const SNIPPET_WORDS_BEFORE: usize = 8;
+/// Maximum number of words in a snippet.
+const SNIPPET_WORDS: usize = 30;
+
+/// A search result, see [`FlatPageStore::search`]
+///
+/// [`FlatPageStore::search`]: crate::FlatPageStore::search
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchHit {
+    /// URL of the matching page
+    pub url: String,
+    /// BM25 relevance score, higher is better
+    pub score: f64,
+    /// HTML-escaped excerpt of the page body with matching words wrapped in
+    /// `<mark>`
+    pub snippet: String,
+}
+
+/// Inverted index of page texts.
+#[derive(Debug, Default)]
+pub(crate) struct SearchIndex {
+    /// Splits texts and queries into terms.
+    tokenizer: Tokenizer,
+    /// Maps terms to weighted term frequencies per page URL.
+    postings: HashMap<String, HashMap<String, f64>>,
+    /// Indexed pages by URL.
+    documents: HashMap<String, Document>,
+    /// Sum of all document lengths, for the average length.
+    total_length: f64,
+}
+
+/// A single indexed page.
+#[derive(Debug)]
+struct Document {
+    /// Weighted number of terms.
+    length: f64,
+    /// Plain-text body used for snippets.
+    body: String,
+}
+
+/// Texts of a page to index.
+pub(crate) struct IndexedText<'a> {
+    /// Plain-text title.
+    pub(crate) title: &'a str,
+    /// Description, if any.
+    pub(crate) description: Option<&'a str>,
+    /// Plain-text body.
+    pub(crate) body: String,
+}
+
+impl SearchIndex {
+    /// Creates an empty index using the tokenizer.
+    pub(crate) fn new(tokenizer: Tokenizer) -> Self {
+        Self {
+            tokenizer,
+            ..Self::default()
+        }
+    }
+
+    /// Adds or replaces a page.
+    pub(crate) fn insert(&mut self, url: &str, text: IndexedText<'_>) {
+        self.remove(url);
+
+        let mut frequencies: HashMap<String, f64> = HashMap::new();
+        let fields = [
+            (text.title, TITLE_WEIGHT),
+            (text.description.unwrap_or_default(), DESCRIPTION_WEIGHT),
+            (text.body.as_str(), 1.0),
+        ];
+        for (field, weight) in fields {
+            for (_, term) in self.tokenizer.tokenize(field) {
+                *frequencies.entry(term).or_default() += weight;
+            }
+        }
+
+        let length = frequencies.values().sum();
+        for (term, frequency) in frequencies {
+            self.postings
+                .entry(term)
+                .or_default()
+                .insert(url.to_string(), frequency);
+        }
+        self.total_length += length;
+        self.documents.insert(
+            url.to_string(),
+            Document {
+                length,
+                body: text.body,
+            },
+        );
+    }
+
+    /// Removes a page, if it's indexed.
+    pub(crate) fn remove(&mut self, url: &str) {
+        let Some(document) = self.documents.remove(url) else {
+            return;
+        };
+        self.total_length -= document.length;
+        self.postings.retain(|_, pages| {
+            pages.remove(url);
+            !pages.is_empty()
+        });
+    }
+
+    /// Returns pages matching any of the query terms, best matches first.
+    pub(crate) fn search(&self, query: &str) -> Vec<SearchHit> {
+        let terms = self
+            .tokenizer
+            .tokenize(query)
+            .map(|(_, term)| term)
+            .collect::<HashSet<_>>();
+        if terms.is_empty() || self.documents.is_empty() {
+            return Vec::new();
+        }
+
+        let count = self.documents.len() as f64;
+        let average_length = (self.total_length / count).max(1.0);
+        let mut scores: HashMap<&str, f64> = HashMap::new();
+        for term in &terms {
+            let Some(pages) = self.postings.get(term) else {
+                continue;
+            };
+            let matches = pages.len() as f64;
+            let idf = (1.0 + (count - matches + 0.5) / (matches + 0.5)).ln();
+            for (url, frequency) in pages {
+                let length = self.documents[url].length;
+                let norm = K1 * (1.0 - B + B * length / average_length);
+                *scores.entry(url).or_default() +=
+                    idf * frequency * (K1 + 1.0) / (frequency + norm);
+            }
+        }
+
+        let mut hits = scores
+            .into_iter()
+            .map(|(url, score)| SearchHit {
+                url: url.to_string(),
+                score,
+                snippet: self.snippet(&self.documents[url].body, &terms),
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.url.cmp(&b.url)));
+        hits
+    }
+
+    /// Builds an HTML snippet around the first matching word of the text.
+    fn snippet(&self, text: &str, terms: &HashSet<String>) -> String {
+        let words = words(text).collect::<Vec<_>>();
+        let first_match = words
+            .iter()
+            .position(|(_, word)| self.is_match(word, terms))
+            .unwrap_or(0);
+        let start = first_match.saturating_sub(SNIPPET_WORDS_BEFORE);
+        let end = words.len().min(start + SNIPPET_WORDS);
+        let Some(window) = words.get(start..end).filter(|window| !window.is_empty()) else {
+            return String::new();
+        };
+
+        let mut snippet = String::new();
+        if start > 0 {
+            snippet.push('…');
+        }
+        let mut cursor = if start > 0 { window[0].0 } else { 0 };
+        for (offset, word) in window {
+            snippet.push_str(&escape_html(&collapse_whitespace(&text[cursor..*offset])));
+            if self.is_match(word, terms) {
+                snippet.push_str("<mark>");
+                snippet.push_str(word);
+                snippet.push_str("</mark>");
+            } else {
+                snippet.push_str(word);
+            }
+            cursor = offset + word.len();
+        }
+        if end < words.len() {
+            snippet.push('…');
+        } else {
+            snippet.push_str(&escape_html(&collapse_whitespace(&text[cursor..])));
+        }
+        snippet.trim().to_string()
+    }
+
+    /// Returns whether a word of a text matches one of the query terms.
+    fn is_match(&self, word: &str, terms: &HashSet<String>) -> bool {
+        self.tokenizer
+            .term(word)
+            .is_some_and(|term| terms.contains(&term))
+    }
+}
+
+/// Replaces whitespace runs with single spaces.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    for (i, part) in text.split_whitespace().enumerate() {
+        if i > 0 {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(part);
+    }
+    if text.starts_with(char::is_whitespace) {
+        collapsed.insert(0, ' ');
+    }
+    if text.ends_with(char::is_whitespace) && !text.trim().is_empty() {
+        collapsed.push(' ');
+    }
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> SearchIndex {
+        let mut index = SearchIndex::default();
+        index.insert(
+            "/install",
+            IndexedText {
+                title: "Install",
+                description: Some("How to install"),
+                body: "Run the installer, then restart.".to_string(),
+            },
+        );
+        index.insert(
+            "/usage",
+            IndexedText {
+                title: "Usage",
+                description: None,
+                body: "After you install the tool & run it.\n\nUse it daily.".to_string(),
+            },
+        );
+        index
+    }
+
+    #[test]
+    fn test_search_ranks_title_matches_first() {
+        let hits = index().search("INSTALL");
+        assert_eq!(
+            hits.iter().map(|hit| hit.url.as_str()).collect::<Vec<_>>(),
+            vec!["/install", "/usage"]
+        );
+        assert!(hits[0].score > hits[1].score);
+        assert_eq!(hits[0].snippet, "Run the installer, then restart.");
+        assert_eq!(
+            hits[1].snippet,
+            "After you <mark>install</mark> the tool &amp; run it. Use it daily."
+        );
+
+        assert!(index().search("").is_empty());
+        assert!(index().search("missing").is_empty());
+    }
+
+    #[test]
+    fn test_search_index_updates() {
+        let mut index = index();
+        index.insert(
+            "/usage",
+            IndexedText {
+                title: "Usage",
+                description: None,
+                body: "Nothing relevant".to_string(),
+            },
+        );
+        assert_eq!(index.search("install").len(), 1);
+
+        index.remove("/install");
+        assert!(index.search("install").is_empty());
+        assert!(index.postings.values().all(|pages| !pages.is_empty()));
+        assert_eq!(index.search("relevant")[0].url, "/usage");
+    }
+
+    #[test]
+    fn test_search_uses_tokenizer() {
+        let mut index = SearchIndex::new(Tokenizer::english());
+        index.insert(
+            "/",
+            IndexedText {
+                title: "Home",
+                description: None,
+                body: "The home page".to_string(),
+            },
+        );
+        assert!(index.search("the").is_empty());
+        assert_eq!(
+            index.search("The HOME")[0].snippet,
+            "The <mark>home</mark> page"
+        );
+    }
+
+    #[test]
+    fn test_snippet() {
+        let terms = HashSet::from(["needle".to_string()]);
+        let text = (1..=50)
+            .map(|i| {
+                if i == 20 {
+                    "needle".to_string()
+                } else {
+                    format!("w{i}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let snippet = SearchIndex::default().snippet(&text, &terms);
+        assert!(snippet.starts_with("…w12 w13"));
+        assert!(snippet.contains("w19 <mark>needle</mark> w21"));
+        assert!(snippet.ends_with("w41…"));
+    }
+}
@@ -0,0 +1,123 @@
+//! Configurable rules for page URL segments and file names.
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick};
+
+use crate::util::is_valid_page_segment;
+
+/// Characters of a page URL segment, also applied to file and folder names
+/// when scanning a store
+///
+/// Whatever the policy, segments can't be empty, `.` or `..`, or contain a
+/// slash.
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub enum SegmentPolicy {
+    /// ASCII letters and digits, `_`, `-` and `.`
+    #[default]
+    Ascii,
+    /// Unicode letters and digits, `_`, `-` and `.`, such as `über-uns`
+    ///
+    /// URLs and file names are normalized to
+    /// [NFC](https://unicode.org/reports/tr15/), so a decomposed `u` with a
+    /// combining diaeresis matches the precomposed `ü`.
+    Unicode,
+    /// Segments accepted by a predicate, see [`SegmentPolicy::custom`]
+    Custom(Arc<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+impl SegmentPolicy {
+    /// Accepts the segments for which the predicate returns `true`.
+    ///
+    /// ```
+    /// use flatpage::SegmentPolicy;
+    ///
+    /// let policy = SegmentPolicy::custom(|segment| {
+    ///     segment.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+    /// });
+    /// assert!(policy.is_valid("getting-started"));
+    /// assert!(!policy.is_valid("Getting-Started"));
+    /// ```
+    pub fn custom(predicate: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(predicate))
+    }
+
+    /// Returns whether a single segment, such as `install`, is accepted.
+    ///
+    /// With [`SegmentPolicy::Unicode`] the segment has to be normalized
+    /// already.
+    pub fn is_valid(&self, segment: &str) -> bool {
+        if segment.is_empty() || segment == "." || segment == ".." || segment.contains('/') {
+            return false;
+        }
+        match self {
+            Self::Ascii => is_valid_page_segment(segment),
+            Self::Unicode => {
+                is_nfc_quick(segment.chars()) == IsNormalized::Yes
+                    && segment
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || "_-.".contains(c))
+            }
+            Self::Custom(predicate) => predicate(segment),
+        }
+    }
+
+    /// Normalizes a URL or a file name, borrowing it when it's unchanged.
+    pub(crate) fn normalize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self {
+            Self::Unicode if is_nfc_quick(text.chars()) != IsNormalized::Yes => {
+                Cow::Owned(text.nfc().collect())
+            }
+            _ => Cow::Borrowed(text),
+        }
+    }
+}
+
+impl fmt::Debug for SegmentPolicy {
+    /// Formats the policy, omitting the predicate of a custom one.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ascii => f.write_str("Ascii"),
+            Self::Unicode => f.write_str("Unicode"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid() {
+        let ascii = SegmentPolicy::Ascii;
+        assert!(ascii.is_valid("v1.2"));
+        assert!(!ascii.is_valid("über-uns"));
+
+        let unicode = SegmentPolicy::Unicode;
+        for segment in ["über-uns", "о-нас", "v1.2", "入门"] {
+            assert!(unicode.is_valid(segment), "{segment:?} should be valid");
+        }
+        for segment in ["", "..", "u\u{308}ber", "a b", "a/b", "foo?"] {
+            assert!(!unicode.is_valid(segment), "{segment:?} should be invalid");
+        }
+
+        let custom = SegmentPolicy::custom(|segment| segment.len() < 4);
+        assert!(custom.is_valid("a b"));
+        assert!(!custom.is_valid("long"));
+        assert!(!custom.is_valid(".."));
+        assert_eq!(format!("{custom:?}"), "Custom(..)");
+    }
+
+    #[test]
+    fn test_normalize() {
+        let decomposed = "/u\u{308}ber-uns";
+        assert_eq!(SegmentPolicy::Unicode.normalize(decomposed), "/über-uns");
+        assert!(matches!(
+            SegmentPolicy::Unicode.normalize("/über-uns"),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(SegmentPolicy::Ascii.normalize(decomposed), decomposed);
+    }
+}
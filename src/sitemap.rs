@@ -0,0 +1,304 @@
+//! `sitemap.xml` generation.
+
+use std::{fs, path::Path};
+
+use crate::{
+    Error, FlatPage, FlatPageStore, Result,
+    util::{Timestamp, escape_html, percent_encode_path},
+};
+
+/// Maximum number of URLs in a single sitemap file allowed by the protocol.
+const MAX_URLS_PER_FILE: usize = 50_000;
+/// XML namespace of sitemap documents.
+const XMLNS: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+
+/// Frontmatter fields affecting sitemap entries.
+#[derive(Debug, serde::Deserialize)]
+struct SitemapFrontmatter {
+    /// Explicit last modification date.
+    updated: Option<Timestamp>,
+    /// Excludes the page from the sitemap.
+    #[serde(default)]
+    noindex: bool,
+    /// Excludes an unpublished page from the sitemap.
+    #[serde(default)]
+    draft: bool,
+}
+
+/// `sitemap.xml` generator
+///
+/// Every page of a store becomes an entry with its URL joined to the base
+/// URL. `<lastmod>` comes from the frontmatter `updated` date, or from the
+/// file modification time. Pages with `noindex: true` or `draft: true` in
+/// frontmatter are skipped.
+///
+/// Past 50 000 URLs, the entries are split into `sitemap-1.xml`,
+/// `sitemap-2.xml`, etc. and `sitemap.xml` becomes a sitemap index.
+#[derive(Clone, Debug)]
+pub struct Sitemap {
+    /// Site URL without a trailing slash, such as `https://example.com`.
+    base_url: String,
+    /// Maximum number of URLs per file.
+    max_urls_per_file: usize,
+}
+
+/// A generated sitemap file, see [`Sitemap::render`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SitemapFile {
+    /// File name, such as `sitemap.xml`
+    pub name: String,
+    /// XML content
+    pub xml: String,
+}
+
+/// A single sitemap entry.
+struct Entry {
+    /// Absolute page URL.
+    loc: String,
+    /// Last modification time.
+    lastmod: Timestamp,
+}
+
+impl Sitemap {
+    /// Creates a generator for a site such as `https://example.com`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        base_url.truncate(base_url.trim_end_matches('/').len());
+        Self {
+            base_url,
+            max_urls_per_file: MAX_URLS_PER_FILE,
+        }
+    }
+
+    /// Lowers the number of URLs per file, 50 000 by default.
+    pub fn max_urls_per_file(mut self, max_urls: usize) -> Self {
+        self.max_urls_per_file = max_urls.clamp(1, MAX_URLS_PER_FILE);
+        self
+    }
+
+    /// Reads every page of the store and renders the sitemap files.
+    ///
+    /// The first file is always `sitemap.xml`.
+    pub fn render(&self, store: &FlatPageStore) -> Result<Vec<SitemapFile>> {
+        let mut entries = Vec::new();
+        for (url, _, path) in store.page_files() {
+            let Some(page) = FlatPage::<SitemapFrontmatter>::by_path(path)? else {
+                continue;
+            };
+            if page.extra.noindex || page.extra.draft {
+                continue;
+            }
+            let lastmod = match page.extra.updated {
+                Some(updated) => updated,
+                None => fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .map_err(|e| Error::read_metadata(e, path))?
+                    .into(),
+            };
+            entries.push(Entry {
+                loc: format!("{}{}", self.base_url, percent_encode_path(url.as_ref())),
+                lastmod,
+            });
+        }
+
+        if entries.len() <= self.max_urls_per_file {
+            return Ok(vec![SitemapFile {
+                name: "sitemap.xml".into(),
+                xml: render_urlset(&entries),
+            }]);
+        }
+
+        let mut files = Vec::new();
+        let mut index = Vec::new();
+        for (i, chunk) in entries.chunks(self.max_urls_per_file).enumerate() {
+            let name = format!("sitemap-{}.xml", i + 1);
+            index.push(Entry {
+                loc: format!("{}/{name}", self.base_url),
+                lastmod: chunk
+                    .iter()
+                    .map(|entry| entry.lastmod)
+                    .max()
+                    .unwrap_or(Timestamp::EPOCH),
+            });
+            files.push(SitemapFile {
+                name,
+                xml: render_urlset(chunk),
+            });
+        }
+        files.insert(
+            0,
+            SitemapFile {
+                name: "sitemap.xml".into(),
+                xml: render_index(&index),
+            },
+        );
+        Ok(files)
+    }
+
+    /// Renders the sitemap files into a directory, creating it if needed.
+    pub fn write(&self, store: &FlatPageStore, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = dir.as_ref();
+        let files = self.render(store)?;
+        fs::create_dir_all(dir).map_err(|e| Error::create_dir(e, dir))?;
+        for file in files {
+            let path = dir.join(&file.name);
+            fs::write(&path, file.xml).map_err(|e| Error::write_file(e, path))?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders a `<urlset>` document.
+fn render_urlset(entries: &[Entry]) -> String {
+    render_document("urlset", "url", entries)
+}
+
+/// Renders a `<sitemapindex>` document.
+fn render_index(entries: &[Entry]) -> String {
+    render_document("sitemapindex", "sitemap", entries)
+}
+
+/// Renders a sitemap or sitemap index document.
+fn render_document(root: &str, element: &str, entries: &[Entry]) -> String {
+    let mut xml =
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<{root} xmlns=\"{XMLNS}\">\n");
+    for entry in entries {
+        xml.push_str(&format!(
+            "  <{element}>\n    <loc>{}</loc>\n    <lastmod>{}</lastmod>\n  </{element}>\n",
+            escape_html(&entry.loc),
+            entry.lastmod.to_date(),
+        ));
+    }
+    xml.push_str(&format!("</{root}>\n"));
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        SegmentPolicy, StoreOptions,
+        test_helpers::{TestDir, write_page},
+    };
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn sitemap_renders_entries() {
+        let root = TestDir::new();
+        write_page(
+            root.path(),
+            "index.md",
+            "---\nupdated: 2024-01-31\n---\n# Home",
+        );
+        write_page(root.path(), "draft.md", "---\ndraft: true\n---\n# Draft");
+        write_page(
+            root.path(),
+            "hidden.md",
+            "---\nnoindex: true\n---\n# Hidden",
+        );
+        write_page(
+            root.path(),
+            "guides/index.md",
+            "---\nupdated: 2024-02-01T10:00:00+00:00\n---\n# Guides",
+        );
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        let files = Sitemap::new("https://example.com/").render(&store).unwrap();
+        assert_eq!(
+            files,
+            vec![SitemapFile {
+                name: "sitemap.xml".into(),
+                xml: "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">
+  <url>
+    <loc>https://example.com/</loc>
+    <lastmod>2024-01-31</lastmod>
+  </url>
+  <url>
+    <loc>https://example.com/guides/</loc>
+    <lastmod>2024-02-01</lastmod>
+  </url>
+</urlset>
+"
+                .into(),
+            }]
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn sitemap_accepts_toml_dates() {
+        let root = TestDir::new();
+        write_page(
+            root.path(),
+            "index.md",
+            "+++\nupdated = 2024-01-31\n+++\n# Home",
+        );
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        let files = Sitemap::new("https://example.com").render(&store).unwrap();
+        assert!(files[0].xml.contains("<lastmod>2024-01-31</lastmod>"));
+    }
+
+    #[test]
+    fn sitemap_uses_file_modification_time() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# Home");
+        let modified = fs::metadata(root.path().join("index.md"))
+            .unwrap()
+            .modified()
+            .unwrap();
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        let files = Sitemap::new("https://example.com").render(&store).unwrap();
+        assert!(files[0].xml.contains(&format!(
+            "<lastmod>{}</lastmod>",
+            Timestamp::from(modified).to_date()
+        )));
+    }
+
+    #[test]
+    fn sitemap_splits_into_index() {
+        let root = TestDir::new();
+        for name in ["a", "b", "c"] {
+            write_page(root.path(), &format!("{name}.md"), "# Page");
+        }
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        let sitemap = Sitemap::new("https://example.com").max_urls_per_file(2);
+        let files = sitemap.render(&store).unwrap();
+        assert_eq!(
+            files
+                .iter()
+                .map(|file| file.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["sitemap.xml", "sitemap-1.xml", "sitemap-2.xml"]
+        );
+        assert!(files[0].xml.contains("<sitemapindex"));
+        assert!(
+            files[0]
+                .xml
+                .contains("<loc>https://example.com/sitemap-2.xml</loc>")
+        );
+        assert!(files[1].xml.contains("<loc>https://example.com/b</loc>"));
+        assert!(files[2].xml.contains("<loc>https://example.com/c</loc>"));
+
+        let out = TestDir::new();
+        sitemap.write(&store, out.path().join("public")).unwrap();
+        assert!(out.path().join("public/sitemap-2.xml").is_file());
+    }
+    #[test]
+    fn sitemap_percent_encodes_unicode_urls() {
+        let root = TestDir::new();
+        write_page(root.path(), "über-uns.md", "# Über uns");
+
+        let options = StoreOptions::new().segment_policy(SegmentPolicy::Unicode);
+        let store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+        let files = Sitemap::new("https://example.com").render(&store).unwrap();
+        assert!(
+            files[0]
+                .xml
+                .contains("<loc>https://example.com/%C3%BCber-uns</loc>")
+        );
+    }
+}
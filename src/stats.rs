@@ -0,0 +1,124 @@
+//! Word counts, reading time and other content statistics.
+
+use std::time::Duration;
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+use crate::markdown::parser_options;
+
+/// Content statistics of a page body, see [`FlatPage::stats`]
+///
+/// Code, raw HTML and image descriptions are not counted as words.
+///
+/// [`FlatPage::stats`]: crate::FlatPage::stats
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize)]
+#[non_exhaustive]
+pub struct PageStats {
+    /// Number of words
+    pub word_count: usize,
+    /// Number of headings
+    pub heading_count: usize,
+    /// Number of fenced and indented code blocks
+    pub code_block_count: usize,
+    /// Number of images
+    pub image_count: usize,
+}
+
+impl PageStats {
+    /// Average adult reading speed used when there's no better estimate.
+    pub const DEFAULT_WORDS_PER_MINUTE: u32 = 200;
+
+    /// Computes statistics of a Markdown text.
+    pub(crate) fn from_markdown(text: &str) -> Self {
+        let mut stats = Self::default();
+        let mut words = String::new();
+        let mut skip_depth = 0_usize;
+        for event in Parser::new_ext(text, parser_options()) {
+            match event {
+                Event::Start(Tag::Heading { .. }) => stats.heading_count += 1,
+                Event::Start(Tag::CodeBlock(_)) => {
+                    stats.code_block_count += 1;
+                    skip_depth += 1;
+                }
+                Event::Start(Tag::Image { .. }) => {
+                    stats.image_count += 1;
+                    skip_depth += 1;
+                }
+                Event::End(TagEnd::CodeBlock | TagEnd::Image) => skip_depth -= 1,
+                Event::Text(text) if skip_depth == 0 => words.push_str(&text),
+                // Inline markup doesn't split words, so `**bold**text` is one.
+                Event::End(
+                    TagEnd::Emphasis
+                    | TagEnd::Strong
+                    | TagEnd::Strikethrough
+                    | TagEnd::Superscript
+                    | TagEnd::Subscript
+                    | TagEnd::Link,
+                ) => {}
+                Event::End(_) | Event::SoftBreak | Event::HardBreak | Event::Code(_) => {
+                    words.push(' ');
+                }
+                _ => {}
+            }
+        }
+        stats.word_count = words
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count();
+        stats
+    }
+
+    /// Estimated reading time at the given speed.
+    ///
+    /// Use [`PageStats::DEFAULT_WORDS_PER_MINUTE`] if unsure.
+    pub fn reading_time(&self, words_per_minute: u32) -> Duration {
+        Duration::from_secs_f64(self.word_count as f64 * 60.0 / f64::from(words_per_minute.max(1)))
+    }
+
+    /// Estimated reading time in whole minutes, rounded up, for "7 min read"
+    /// labels.
+    pub fn reading_minutes(&self, words_per_minute: u32) -> u64 {
+        (self.word_count as u64).div_ceil(u64::from(words_per_minute.max(1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::PageStats;
+
+    #[test]
+    fn test_from_markdown() {
+        assert_eq!(
+            PageStats::from_markdown(
+                "# Hello *wide* world\n\nSome `inline code` and\n**bold**text — \
+                 ok.\n\n![alt text](x.png)\n\n```rust\nfn main() {}\n```\n\n    indented\n\n## \
+                 Next\n\n<div>raw html</div>"
+            ),
+            PageStats {
+                word_count: 8,
+                heading_count: 2,
+                code_block_count: 2,
+                image_count: 1,
+            }
+        );
+        assert_eq!(
+            PageStats::from_markdown("**bold**text foo*bar*baz [a](x)b\n\n- one\n- two").word_count,
+            5
+        );
+        assert_eq!(PageStats::from_markdown(""), PageStats::default());
+    }
+
+    #[test]
+    fn test_reading_time() {
+        let stats = PageStats {
+            word_count: 450,
+            ..PageStats::default()
+        };
+        assert_eq!(stats.reading_time(200), Duration::from_secs(135));
+        assert_eq!(stats.reading_minutes(200), 3);
+        assert_eq!(stats.reading_minutes(450), 1);
+        assert_eq!(PageStats::default().reading_minutes(200), 0);
+    }
+}
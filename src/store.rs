@@ -9,8 +9,17 @@ use std::{
 use serde::de::DeserializeOwned;
 
 use crate::{
-    Error, FlatPage, Result,
-    util::{AbsPagePath, NormalizedUrl, RelPagePath},
+    BrokenLink, CacheValidators, ClientSearchIndex, Error, FlatPage, Lookup, NavLink, PageContext,
+    PageMapping, PageStats, PageUrl, RenderOptions, Result, SearchHit, SegmentPolicy, Tokenizer,
+    links,
+    markdown::{inline_markdown_to_html, inline_markdown_to_text},
+    redirect::{
+        BrokenRedirect, REDIRECTS_FILE, Redirect, RedirectFrontmatter, RedirectRule,
+        RedirectTarget, parse_redirects,
+    },
+    search::{IndexedText, SearchIndex},
+    suggest,
+    util::{AbsPagePath, NormalizedUrl, is_external_url},
 };
 
 /// A store for [`FlatPageMeta`]
@@ -18,26 +27,251 @@ use crate::{
 pub struct FlatPageStore {
     /// The folder containing markdown pages
     root: PathBuf,
+    /// Options the store was created with, reused for refreshed pages.
+    options: StoreOptions,
     /// Maps normalized URLs such as `/guides/install` to metadata.
-    pages: HashMap<NormalizedUrl<'static>, FlatPageMeta>,
+    pages: HashMap<PageUrl, FlatPageMeta>,
+    /// Maps page URLs to the files they were read from.
+    paths: HashMap<PageUrl, PathBuf>,
+    /// Maps old URLs to redirects declared in frontmatter.
+    redirects: HashMap<PageUrl, Redirect>,
+    /// Rules of the `_redirects` file, in file order.
+    rules: Vec<RedirectRule>,
+    /// Maps lowercased URLs to page URLs, if enabled by
+    /// [`StoreOptions::case_insensitive`].
+    folded: HashMap<String, PageUrl>,
+    /// Maps lowercased redirect sources to the sources in
+    /// [`FlatPageStore::redirects`], if enabled by
+    /// [`StoreOptions::case_insensitive`].
+    folded_redirects: HashMap<String, PageUrl>,
+    /// Full-text index, if enabled by [`StoreOptions::search_index`].
+    search: Option<SearchIndex>,
 }
 
 /// Flat page metadata
-#[derive(Debug)]
+///
+/// Fields are added as the store caches more data, so the struct can't be
+/// built or exhaustively destructured outside the crate.
+#[derive(Debug, serde::Serialize)]
+#[non_exhaustive]
 pub struct FlatPageMeta {
     /// Page title
     pub title: String,
     /// Page description
     pub description: Option<String>,
+    /// Plain-text [`FlatPage::summary`], cached if enabled by
+    /// [`StoreOptions::summaries`]
+    pub summary: Option<String>,
+    /// [`FlatPage::stats`], cached if enabled by [`StoreOptions::stats`]
+    pub stats: Option<PageStats>,
+    /// [`FlatPage::validators`], cached if enabled by
+    /// [`StoreOptions::cache_validators`]
+    #[serde(skip)]
+    pub validators: Option<CacheValidators>,
+}
+
+/// Options for [`FlatPageStore::read_dir_with`]
+#[derive(Clone, Debug, Default)]
+pub struct StoreOptions {
+    /// Whether to cache plain-text summaries.
+    summaries: bool,
+    /// Whether to cache content statistics.
+    stats: bool,
+    /// Whether to build a full-text search index.
+    search_index: bool,
+    /// Whether to cache HTTP cache validators.
+    cache_validators: bool,
+    /// Splits texts and queries into search terms.
+    tokenizer: Tokenizer,
+    /// How URLs map to page files.
+    mapping: PageMapping,
+    /// Whether lookups ignore letter case.
+    case_insensitive: bool,
+}
+
+impl StoreOptions {
+    /// Creates the default options, the same as used by
+    /// [`FlatPageStore::read_dir`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caches plain-text summaries in [`FlatPageMeta::summary`].
+    pub fn summaries(mut self, enabled: bool) -> Self {
+        self.summaries = enabled;
+        self
+    }
+
+    /// Caches word counts and other content statistics in
+    /// [`FlatPageMeta::stats`].
+    pub fn stats(mut self, enabled: bool) -> Self {
+        self.stats = enabled;
+        self
+    }
+
+    /// Caches content hashes and modification times in
+    /// [`FlatPageMeta::validators`], so conditional requests can be answered
+    /// without reading page files.
+    ///
+    /// The cached values are only updated by [`FlatPageStore::refresh_url`].
+    pub fn cache_validators(mut self, enabled: bool) -> Self {
+        self.cache_validators = enabled;
+        self
+    }
+
+    /// Builds an in-memory full-text index of titles, descriptions and bodies
+    /// for [`FlatPageStore::search`].
+    pub fn search_index(mut self, enabled: bool) -> Self {
+        self.search_index = enabled;
+        self
+    }
+
+    /// Sets the tokenizer of the search index, [`Tokenizer::new`] by default.
+    pub fn tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Sets the characters allowed in URL segments and in the names of page
+    /// files and folders, [`SegmentPolicy::Ascii`] by default.
+    ///
+    /// Files with other names are skipped by the scan, and URLs with other
+    /// segments aren't found.
+    ///
+    /// A shorthand for setting the policy of [`StoreOptions::page_mapping`].
+    pub fn segment_policy(mut self, policy: SegmentPolicy) -> Self {
+        self.mapping.segment_policy = policy;
+        self
+    }
+
+    /// Sets how URLs map to page files, such as `README.md` indexes or
+    /// `.markdown` extensions, see [`PageMapping`].
+    ///
+    /// When several files map to the same URL, only the most preferred one is
+    /// loaded, both by the scan and by [`FlatPageStore::refresh_url`].
+    pub fn page_mapping(mut self, mapping: PageMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    /// Makes [`FlatPageStore::lookup`] ignore letter case, redirecting
+    /// `/Guides/Install` to `/guides/install`. Frontmatter redirects then also
+    /// match their source URLs in any case.
+    ///
+    /// Page files whose URLs differ only in case, such as `Install.md` and
+    /// `install.md`, are then reported as [`Error::CaseConflict`].
+    pub fn case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
 }
 
 impl FlatPageStore {
     /// Creates a store by scanning the folder recursively.
     pub fn read_dir(root: impl Into<PathBuf>) -> Result<Self> {
+        Self::read_dir_with(root, &StoreOptions::default())
+    }
+
+    /// Creates a store by scanning the folder recursively with the given
+    /// options.
+    pub fn read_dir_with(root: impl Into<PathBuf>, options: &StoreOptions) -> Result<Self> {
         let root = root.into();
-        let mut pages = HashMap::new();
-        read_dir_recursive(&root, &root, &mut pages)?;
-        Ok(Self { root, pages })
+        let mut files = Vec::new();
+        read_dir_recursive(&root, &root, &options.mapping, &mut files)?;
+        // Sorted, so case conflicts are reported the same way every time, and
+        // the most preferred of the files mapping to a URL comes first.
+        files.sort();
+        files.dedup_by(|(next, ..), (kept, ..)| next == kept);
+        let mut store = Self {
+            root,
+            options: options.clone(),
+            pages: HashMap::new(),
+            paths: HashMap::new(),
+            folded: HashMap::new(),
+            folded_redirects: HashMap::new(),
+            redirects: HashMap::new(),
+            rules: Vec::new(),
+            search: options
+                .search_index
+                .then(|| SearchIndex::new(options.tokenizer.clone())),
+        };
+        for (url, _, path) in files {
+            store.load_page(url, path)?;
+        }
+        store.check_redirect_conflicts()?;
+        store.refresh_redirects()?;
+        Ok(store)
+    }
+
+    /// Re-reads a single page after it was created, changed or removed.
+    ///
+    /// Cached metadata and the search index are updated, or the page is
+    /// dropped from the store if its file no longer exists. Invalid URLs are
+    /// ignored.
+    pub fn refresh_url(&mut self, url: impl AsRef<str>) -> Result<()> {
+        let Some(url) = self.parse_url(url.as_ref()) else {
+            return Ok(());
+        };
+        let url = PageUrl::from_normalized(url);
+        let candidates =
+            AbsPagePath::candidates(&self.root, url.normalized(), &self.options.mapping);
+        let path = candidates
+            .iter()
+            .map(|path| path.as_ref())
+            .find(|path| path.exists())
+            .or_else(|| self.paths.get(&url).map(PathBuf::as_path))
+            .or_else(|| candidates.first().map(|path| path.as_ref()))
+            .map(Path::to_path_buf);
+        // A mapping without index names or extensions has no files at all.
+        let Some(path) = path else {
+            self.remove_page(&url);
+            return Ok(());
+        };
+        // Symlinked directories are skipped by the scan, so pages inside them
+        // are treated as missing here too.
+        let in_symlinked_dir = path
+            .strip_prefix(&self.root)
+            .unwrap_or(&path)
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .any(|dir| {
+                fs::symlink_metadata(self.root.join(dir)).is_ok_and(|m| m.file_type().is_symlink())
+            });
+        if in_symlinked_dir {
+            self.remove_page(&url);
+            return Ok(());
+        }
+        self.load_page(url, path)?;
+        self.check_redirect_conflicts()
+    }
+
+    /// Re-reads the `_redirects` file in the pages root after it was
+    /// created, changed or removed.
+    ///
+    /// Each line is a rule with a source, a destination and an optional
+    /// status, such as `/blog/* /news/:splat 302`. Lines starting with `#`
+    /// are comments.
+    pub fn refresh_redirects(&mut self) -> Result<()> {
+        let path = self.root.join(REDIRECTS_FILE);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(Error::read_file(e, path)),
+        };
+        self.rules = parse_redirects(&content, &self.options.mapping.segment_policy).map_err(
+            |(line, problem)| Error::InvalidRedirectRule {
+                path,
+                line,
+                problem,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Returns the directory the pages are read from.
+    pub fn root(&self) -> &Path {
+        &self.root
     }
 
     /// Returns page metadata by URL.
@@ -46,8 +280,8 @@ impl FlatPageStore {
     /// `/foo/` looks up `foo/index.md`.
     ///
     /// Returns `None` for invalid URLs and missing pages.
-    pub fn meta_by_url(&self, url: &str) -> Option<&FlatPageMeta> {
-        let url = NormalizedUrl::try_from(url).ok()?;
+    pub fn meta_by_url(&self, url: impl AsRef<str>) -> Option<&FlatPageMeta> {
+        let url = self.parse_url(url.as_ref())?;
         self.pages.get(url.as_ref())
     }
 
@@ -57,18 +291,107 @@ impl FlatPageStore {
     /// `/foo/` looks up `foo/index.md`.
     ///
     /// Returns `false` for invalid URLs and missing pages.
-    pub fn contains_url(&self, url: &str) -> bool {
-        let Ok(url) = NormalizedUrl::try_from(url) else {
+    pub fn contains_url(&self, url: impl AsRef<str>) -> bool {
+        let Some(url) = self.parse_url(url.as_ref()) else {
             return false;
         };
         self.pages.contains_key(url.as_ref())
     }
 
-    /// Iterates over cached metadata without exposing the internal URL type.
+    /// Looks up a page, suggesting a redirect when only the other
+    /// trailing-slash form exists.
     ///
-    /// The iteration order is unspecified.
+    /// Unlike [`FlatPageStore::meta_by_url`], a miss on `/guides` returns
+    /// [`Lookup::Redirect`] to `/guides/` if that page exists, and the other
+    /// way round. With [`StoreOptions::case_insensitive`], URLs differing
+    /// only in case redirect to the page too.
+    ///
+    /// Old URLs listed in the `aliases` or `redirect_from` frontmatter of a
+    /// page redirect to it, and the URL of a page with `redirect_to` redirects
+    /// to the target, also from the other trailing-slash form. A `redirect_to`
+    /// URL of another site gives a [`Lookup::Rule`] with status 301. Other
+    /// misses are looked up in the `_redirects` file, where the first
+    /// matching rule gives a [`Lookup::Rule`].
+    pub fn lookup(&self, url: impl AsRef<str>) -> Lookup<'_> {
+        let Some(url) = self.parse_url(url.as_ref()) else {
+            return Lookup::NotFound;
+        };
+        if let Some((url, meta)) = self.pages.get_key_value(url.as_ref()) {
+            return Lookup::Found { url, meta };
+        }
+        if let Some(redirect) = self.redirects.get(url.as_ref()) {
+            return redirect.lookup();
+        }
+        if let Some((location, rule)) = self
+            .rules
+            .iter()
+            .find_map(|rule| Some((rule.apply(url.as_ref())?, rule)))
+        {
+            return Lookup::Rule {
+                location,
+                status: rule.status,
+            };
+        }
+        let url = url.as_ref();
+        let alternate = match url.strip_suffix('/') {
+            Some("") => None,
+            Some(url) => Some(url.to_string()),
+            None => Some(format!("{url}/")),
+        };
+        if let Some((canonical, _)) = alternate
+            .as_deref()
+            .and_then(|alternate| self.pages.get_key_value(alternate))
+        {
+            return Lookup::Redirect(canonical.clone());
+        }
+        if let Some(redirect) = alternate
+            .as_deref()
+            .and_then(|alternate| self.redirects.get(alternate))
+        {
+            return redirect.lookup();
+        }
+        if self.options.case_insensitive {
+            let candidates = || std::iter::once(url).chain(alternate.as_deref());
+            if let Some(canonical) =
+                candidates().find_map(|candidate| self.folded.get(&candidate.to_lowercase()))
+            {
+                return Lookup::Redirect(canonical.clone());
+            }
+            if let Some(redirect) = candidates()
+                .filter_map(|candidate| self.folded_redirects.get(&candidate.to_lowercase()))
+                .find_map(|from| self.redirects.get(from))
+            {
+                return redirect.lookup();
+            }
+        }
+        Lookup::NotFound
+    }
+
+    /// Looks up the path of a request URI, see [`PageUrl::from_request_uri`]
+    /// and [`FlatPageStore::lookup`].
+    ///
+    /// Returns [`Error::InvalidUrl`] for malformed URIs, so they can be told
+    /// apart from missing pages.
+    pub fn lookup_request(&self, uri: &str) -> Result<Lookup<'_>> {
+        Ok(self.lookup(PageUrl::from_request_uri_with(
+            uri,
+            &self.options.mapping.segment_policy,
+        )?))
+    }
+
+    /// Iterates over page URLs as strings and cached metadata, see
+    /// [`FlatPageStore::pages`] for [`PageUrl`] keys.
+    ///
+    /// The iteration order is unspecified, sort by URL for a stable listing.
     pub fn iter(&self) -> impl Iterator<Item = (&str, &FlatPageMeta)> + '_ {
-        self.pages.iter().map(|(url, meta)| (url.as_ref(), meta))
+        self.pages.iter().map(|(url, meta)| (url.as_str(), meta))
+    }
+
+    /// Iterates over page URLs and cached metadata.
+    ///
+    /// The iteration order is unspecified.
+    pub fn pages(&self) -> impl Iterator<Item = (&PageUrl, &FlatPageMeta)> + '_ {
+        self.pages.iter()
     }
 
     /// Returns a page by URL.
@@ -77,18 +400,466 @@ impl FlatPageStore {
     /// `/foo/` looks up `foo/index.md`.
     ///
     /// Returns `Ok(None)` for invalid URLs and missing pages.
-    pub fn page_by_url<E: DeserializeOwned>(&self, url: &str) -> Result<Option<FlatPage<E>>> {
-        let Ok(url) = NormalizedUrl::try_from(url) else {
+    pub fn page_by_url<E: DeserializeOwned>(
+        &self,
+        url: impl AsRef<str>,
+    ) -> Result<Option<FlatPage<E>>> {
+        let Some(url) = self.parse_url(url.as_ref()) else {
             return Ok(None);
         };
         // Intentionally check the in-memory index first so missing pages avoid
         // filesystem access.
-        if !self.pages.contains_key(url.as_ref()) {
+        let Some(path) = self.paths.get(url.as_ref()) else {
             return Ok(None);
+        };
+        FlatPage::by_path(path)
+    }
+
+    /// Returns links to up to `n` pages with URLs or titles similar to a
+    /// missing URL, best first, such as for a "did you mean" list on a 404
+    /// page.
+    ///
+    /// URLs are compared segment by segment by edit distance, and the last
+    /// segment is also compared with page titles. Only cached metadata is
+    /// used, and the URL doesn't have to be valid. Only the start of long
+    /// URLs and segments is compared.
+    pub fn suggest(&self, url: impl AsRef<str>, n: usize) -> Vec<NavLink> {
+        suggest::suggest(url.as_ref(), self.pages.iter(), n)
+            .into_iter()
+            .filter_map(|url| self.nav_link(url.as_str()))
+            .collect()
+    }
+
+    /// Returns links to the folder index pages above a page, starting from
+    /// `/`, followed by the page itself.
+    ///
+    /// Missing index pages are skipped. Returns an empty list for invalid URLs.
+    pub fn breadcrumbs(&self, url: impl AsRef<str>) -> Vec<NavLink> {
+        let Some(url) = self.parse_url(url.as_ref()) else {
+            return Vec::new();
+        };
+        let url = url.as_ref();
+        let mut ancestors = url
+            .match_indices('/')
+            .map(|(i, _)| &url[..=i])
+            .collect::<Vec<_>>();
+        if !url.ends_with('/') {
+            ancestors.push(url);
         }
+        ancestors
+            .into_iter()
+            .filter_map(|ancestor| self.nav_link(ancestor))
+            .collect()
+    }
 
-        let path = AbsPagePath::from_normalized_url(&self.root, &url);
-        FlatPage::by_path(path)
+    /// Returns links to the pages directly inside a folder, such as
+    /// `/guides/install` and `/guides/advanced/` for `/guides/`, sorted by
+    /// URL.
+    ///
+    /// Returns an empty list for invalid URLs and URLs without a trailing
+    /// slash.
+    pub fn children(&self, url: impl AsRef<str>) -> Vec<NavLink> {
+        let Some(url) = self.parse_url(url.as_ref()) else {
+            return Vec::new();
+        };
+        let url = url.as_ref();
+        if !url.ends_with('/') {
+            return Vec::new();
+        }
+        let mut children = self
+            .pages
+            .keys()
+            .map(|page_url| page_url.as_ref())
+            .filter(|page_url| {
+                page_url.strip_prefix(url).is_some_and(|rest| {
+                    let rest = rest.strip_suffix('/').unwrap_or(rest);
+                    !rest.is_empty() && !rest.contains('/')
+                })
+            })
+            .collect::<Vec<_>>();
+        children.sort_unstable();
+        children
+            .into_iter()
+            .filter_map(|child| self.nav_link(child))
+            .collect()
+    }
+
+    /// Reads a page and collects the data for rendering it with a template.
+    ///
+    /// Returns `Ok(None)` for invalid URLs and missing pages.
+    pub fn page_context<E: DeserializeOwned>(
+        &self,
+        url: impl AsRef<str>,
+    ) -> Result<Option<PageContext<'_, E>>> {
+        self.page_context_with(url, &RenderOptions::default())
+    }
+
+    /// Reads a page and collects the data for rendering it with a template,
+    /// rendering the body with the given options, see
+    /// [`FlatPageStore::page_context`].
+    pub fn page_context_with<E: DeserializeOwned>(
+        &self,
+        url: impl AsRef<str>,
+        options: &RenderOptions,
+    ) -> Result<Option<PageContext<'_, E>>> {
+        let Some((url, meta)) = self
+            .parse_url(url.as_ref())
+            .and_then(|url| self.pages.get_key_value(url.as_ref()))
+        else {
+            return Ok(None);
+        };
+        let url = url.as_str();
+        let Some(page) = self.page_by_url::<E>(url)? else {
+            return Ok(None);
+        };
+        Ok(Some(PageContext {
+            url: url.to_string(),
+            content: page.html_with(options),
+            toc: page.headings_with(options),
+            page,
+            meta,
+            breadcrumbs: self.breadcrumbs(url),
+            children: self.children(url),
+        }))
+    }
+
+    /// Searches titles, descriptions and bodies, best matches first.
+    ///
+    /// Query words match whole words case-insensitively, and pages matching
+    /// any of them are returned. Title matches rank higher than description
+    /// matches, which rank higher than body matches.
+    ///
+    /// Returns nothing unless the index is enabled by
+    /// [`StoreOptions::search_index`].
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        self.search
+            .as_ref()
+            .map_or_else(Vec::new, |index| index.search(query))
+    }
+
+    /// Reads every page and builds a compact index for client-side search.
+    ///
+    /// Unlike [`FlatPageStore::search`], this doesn't need
+    /// [`StoreOptions::search_index`].
+    pub fn client_search_index(&self, tokenizer: &Tokenizer) -> Result<ClientSearchIndex> {
+        ClientSearchIndex::build(self.page_files(), tokenizer)
+    }
+
+    /// Checks internal links and `#fragment` references of every page.
+    ///
+    /// Each page is read from disk. Links with a scheme (`https:`, `mailto:`)
+    /// or a host (`//example.com`) are ignored. Relative links are resolved
+    /// against the page URL the same way a browser would. A link to a URL
+    /// outside the store is only reported when there's also no file at the
+    /// matching path, so links to images and other assets are accepted.
+    ///
+    /// Anchors are explicit heading ids (`# Foo {#foo}`) and `id` or `name`
+    /// attributes of raw HTML tags.
+    ///
+    /// The findings are sorted by source URL and line.
+    pub fn check_links(&self) -> Result<Vec<BrokenLink>> {
+        links::check_links(
+            &self.root,
+            &self.options.mapping.segment_policy,
+            self.paths
+                .iter()
+                .map(|(url, path)| (url.as_str(), path.as_path())),
+        )
+    }
+
+    /// Checks that redirects from frontmatter and the `_redirects` file point
+    /// at pages, other redirects or files in the pages root.
+    ///
+    /// Redirects to other sites and rule destinations with `:splat` aren't
+    /// checked. The findings are sorted by file and line.
+    pub fn check_redirects(&self) -> Vec<BrokenRedirect> {
+        let mut broken = self
+            .redirects
+            .iter()
+            .filter(|(_, redirect)| match &redirect.target {
+                RedirectTarget::Page(url) => !self.target_exists(url.as_str()),
+                RedirectTarget::External(_) => false,
+            })
+            .map(|(from, redirect)| BrokenRedirect {
+                from: from.to_string(),
+                to: redirect.target.as_str().to_string(),
+                path: redirect.path.clone(),
+                line: None,
+            })
+            .collect::<Vec<_>>();
+        for rule in &self.rules {
+            let to = rule.to.split(['?', '#']).next().unwrap_or_default();
+            if rule.is_external() || to.contains(":splat") || self.target_exists(to) {
+                continue;
+            }
+            broken.push(BrokenRedirect {
+                from: if rule.splat {
+                    format!("{}*", rule.from)
+                } else {
+                    rule.from.clone()
+                },
+                to: rule.to.clone(),
+                path: self.root.join(REDIRECTS_FILE),
+                line: Some(rule.line),
+            });
+        }
+        broken.sort_by(|a, b| (&a.path, a.line, &a.from).cmp(&(&b.path, b.line, &b.from)));
+        broken
+    }
+}
+
+impl FlatPageStore {
+    /// Returns the mapping of page files to URLs.
+    pub(crate) fn mapping(&self) -> &PageMapping {
+        &self.options.mapping
+    }
+
+    /// Returns pages with their metadata and file paths, sorted by URL.
+    pub(crate) fn page_files(&self) -> Vec<(&PageUrl, &FlatPageMeta, &Path)> {
+        let mut pages = self
+            .pages
+            .iter()
+            .map(|(url, meta)| (url, meta, self.paths[url].as_path()))
+            .collect::<Vec<_>>();
+        pages.sort_by_key(|(url, ..)| *url);
+        pages
+    }
+
+    /// Reads a page file and caches its metadata, or removes the page if the
+    /// file doesn't exist.
+    ///
+    /// Redirects and case conflicts are validated before anything is
+    /// changed, so a failed load leaves the store as it was.
+    fn load_page(&mut self, url: PageUrl, path: PathBuf) -> Result<()> {
+        let Some(page) = FlatPage::<RedirectFrontmatter>::by_path(&path)? else {
+            self.remove_redirects(&path);
+            self.remove_page(&url);
+            return Ok(());
+        };
+        let redirect_to = page
+            .extra
+            .redirect_to
+            .as_deref()
+            .map(|target| match is_external_url(target) {
+                true => Ok(RedirectTarget::External(target.to_string())),
+                false => self
+                    .parse_redirect(&url, target, &path)
+                    .map(RedirectTarget::Page),
+            })
+            .transpose()?;
+        let target = redirect_to
+            .clone()
+            .unwrap_or_else(|| RedirectTarget::Page(url.clone()));
+        let mut redirects = page
+            .extra
+            .aliases
+            .iter()
+            .map(|alias| Ok((self.parse_redirect(&url, alias, &path)?, target.clone())))
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(target) = &redirect_to {
+            redirects.push((url.clone(), target.clone()));
+        }
+        self.check_new_redirects(&url, redirect_to.is_none(), &redirects, &path)?;
+        let folded = url.as_str().to_lowercase();
+        let case_conflict = self.folded.get(&folded).filter(|other| {
+            self.options.case_insensitive && redirect_to.is_none() && **other != url
+        });
+        if let Some(other) = case_conflict {
+            return Err(Error::CaseConflict {
+                first: self.paths[other].clone(),
+                second: path,
+            });
+        }
+
+        self.remove_redirects(&path);
+        for (from, target) in redirects {
+            self.insert_redirect(from, target, &path);
+        }
+        if redirect_to.is_some() {
+            self.remove_page(&url);
+            return Ok(());
+        }
+        if self.options.case_insensitive {
+            self.folded.insert(folded, url.clone());
+        }
+        if let Some(search) = &mut self.search {
+            search.insert(
+                url.as_ref(),
+                IndexedText {
+                    title: &page.title_text(),
+                    description: page.description.as_deref(),
+                    body: page.plain_text(),
+                },
+            );
+        }
+        self.pages
+            .insert(url.clone(), FlatPageMeta::from_page(page, &self.options));
+        self.paths.insert(url, path);
+        Ok(())
+    }
+
+    /// Checks the redirects a page file declares against the redirects of
+    /// other files and against pages, before they replace the file's old
+    /// ones.
+    fn check_new_redirects(
+        &self,
+        url: &PageUrl,
+        is_page: bool,
+        redirects: &[(PageUrl, RedirectTarget)],
+        path: &Path,
+    ) -> Result<()> {
+        let conflict = |url: &PageUrl, first: &Path| Error::RedirectConflict {
+            url: url.to_string(),
+            first: first.to_path_buf(),
+            second: path.to_path_buf(),
+        };
+        if let Some(other) = self
+            .redirects
+            .get(url)
+            .filter(|other| is_page && other.path != path)
+        {
+            return Err(Error::RedirectConflict {
+                url: url.to_string(),
+                first: path.to_path_buf(),
+                second: other.path.clone(),
+            });
+        }
+        for (i, (from, _)) in redirects.iter().enumerate() {
+            if redirects[..i].iter().any(|(other, _)| other == from) {
+                return Err(conflict(from, path));
+            }
+            if let Some(other) = self.redirects.get(from).filter(|other| other.path != path) {
+                return Err(conflict(from, &other.path));
+            }
+            if let Some(page_path) = self.paths.get(from).filter(|_| is_page || from != url) {
+                return Err(conflict(from, page_path));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the redirects declared by a page file.
+    fn remove_redirects(&mut self, path: &Path) {
+        self.redirects.retain(|_, redirect| redirect.path != path);
+        self.folded_redirects
+            .retain(|_, from| self.redirects.contains_key(from));
+    }
+
+    /// Resolves a redirect URL from the frontmatter of a page, either
+    /// absolute or relative to the page.
+    fn parse_redirect(&self, page_url: &PageUrl, url: &str, path: &Path) -> Result<PageUrl> {
+        let policy = &self.options.mapping.segment_policy;
+        let resolved = if url.starts_with('/') {
+            PageUrl::parse_with(url, policy)
+        } else {
+            page_url.join_with(url, policy)
+        };
+        resolved.map_err(|e| match e {
+            Error::InvalidUrl { problem, .. } => Error::InvalidRedirect {
+                path: path.to_path_buf(),
+                url: url.to_string(),
+                problem,
+            },
+            e => e,
+        })
+    }
+
+    /// Indexes a redirect checked by [`FlatPageStore::check_new_redirects`].
+    fn insert_redirect(&mut self, from: PageUrl, target: RedirectTarget, path: &Path) {
+        if self.options.case_insensitive {
+            self.folded_redirects
+                .entry(from.as_str().to_lowercase())
+                .or_insert_with(|| from.clone());
+        }
+        let path = path.to_path_buf();
+        self.redirects.insert(from, Redirect { target, path });
+    }
+
+    /// Returns whether a redirect target is a page, redirects further or is a
+    /// file in the pages root.
+    fn target_exists(&self, url: &str) -> bool {
+        !matches!(self.lookup(url), Lookup::NotFound)
+            || self.root.join(url.trim_start_matches('/')).is_file()
+    }
+
+    /// Checks that no redirect starts at the URL of a page.
+    fn check_redirect_conflicts(&self) -> Result<()> {
+        let conflict = self
+            .redirects
+            .iter()
+            .filter_map(|(from, redirect)| Some((from, redirect, self.paths.get(from)?)))
+            .min_by_key(|(from, ..)| *from);
+        match conflict {
+            Some((from, redirect, page_path)) => Err(Error::RedirectConflict {
+                url: from.to_string(),
+                first: page_path.clone(),
+                second: redirect.path.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Normalizes and validates a URL with the segment policy of the store.
+    fn parse_url<'a>(&self, url: &'a str) -> Option<NormalizedUrl<'a>> {
+        NormalizedUrl::parse(url, &self.options.mapping.segment_policy).ok()
+    }
+
+    /// Returns a navigation link to a cached page.
+    fn nav_link(&self, url: &str) -> Option<NavLink> {
+        self.pages.get(url).map(|meta| NavLink {
+            url: url.to_string(),
+            title: meta.title_text(),
+        })
+    }
+
+    /// Drops a page from the cached metadata and the search index.
+    fn remove_page(&mut self, url: &PageUrl) {
+        self.pages.remove(url);
+        self.paths.remove(url);
+        if self.options.case_insensitive {
+            let folded = url.as_str().to_lowercase();
+            if self.folded.get(&folded) == Some(url) {
+                self.folded.remove(&folded);
+            }
+        }
+        if let Some(search) = &mut self.search {
+            search.remove(url.as_ref());
+        }
+    }
+}
+
+impl FlatPageMeta {
+    /// [`FlatPageMeta::title`] with inline Markdown stripped and entities
+    /// decoded
+    pub fn title_text(&self) -> String {
+        inline_markdown_to_text(&self.title)
+    }
+
+    /// [`FlatPageMeta::title`] with inline Markdown rendered to HTML
+    pub fn title_html(&self) -> String {
+        inline_markdown_to_html(&self.title)
+    }
+
+    /// [`FlatPageMeta::description`] or, if it's missing, the cached
+    /// [`FlatPageMeta::summary`]
+    pub fn meta_description(&self) -> Option<&str> {
+        self.description.as_deref().or(self.summary.as_deref())
+    }
+
+    /// Converts a full page into metadata, caching what the options enable.
+    fn from_page<Extra: DeserializeOwned>(page: FlatPage<Extra>, options: &StoreOptions) -> Self {
+        let summary = options
+            .summaries
+            .then(|| page.summary().map(|summary| summary.text()))
+            .flatten();
+        let stats = options.stats.then(|| page.stats());
+        let validators = options.cache_validators.then_some(page.validators);
+        Self {
+            summary,
+            stats,
+            validators,
+            ..page.into()
+        }
     }
 }
 
@@ -98,28 +869,32 @@ impl<Extra> From<FlatPage<Extra>> for FlatPageMeta {
         Self {
             title: p.title,
             description: p.description,
+            summary: None,
+            stats: None,
+            validators: None,
         }
     }
 }
 
-/// Recursively walks the page tree and records metadata for valid Markdown
-/// files.
+/// Recursively walks the page tree and collects URLs and paths of valid
+/// Markdown files.
 ///
-/// This intentionally performs a full scan by reading and parsing the entire
-/// content of each Markdown file to extract the title and description. That
+/// The store then intentionally performs a full scan by reading and parsing
+/// the entire content of each file to extract the title and description. That
 /// keeps the implementation simple at the cost of upfront I/O and parsing.
 fn read_dir_recursive(
     root: &Path,
     dir: &Path,
-    pages: &mut HashMap<NormalizedUrl<'static>, FlatPageMeta>,
+    mapping: &PageMapping,
+    files: &mut Vec<(PageUrl, (usize, usize), PathBuf)>,
 ) -> Result<()> {
     for entry in fs::read_dir(dir).map_err(|e| Error::read_dir(e, dir))? {
         let entry = entry.map_err(|e| Error::read_dir(e, dir))?;
         let path = entry.path();
         let file_type = entry.file_type().map_err(|e| Error::read_dir(e, dir))?;
-        match StoreEntryKind::classify(&path, &file_type)? {
+        match StoreEntryKind::classify(&path, &file_type, mapping)? {
             StoreEntryKind::Directory => {
-                read_dir_recursive(root, &path, pages)?;
+                read_dir_recursive(root, &path, mapping, files)?;
                 continue;
             }
             StoreEntryKind::MarkdownFile => {}
@@ -128,16 +903,15 @@ fn read_dir_recursive(
         let Ok(relative_path) = path.strip_prefix(root) else {
             continue;
         };
-        let Ok(rel_path) = RelPagePath::try_from(relative_path) else {
-            continue;
-        };
-        let Ok(url) = NormalizedUrl::try_from(&rel_path) else {
+        let Ok(url) = NormalizedUrl::from_path(relative_path, mapping) else {
             continue;
         };
-        let Some(page_meta) = FlatPage::<()>::by_path(&path)?.map(Into::into) else {
-            continue;
-        };
-        pages.insert(url, page_meta);
+        let file_name = entry.file_name();
+        let preference = file_name
+            .to_str()
+            .and_then(|name| mapping.preference(name))
+            .unwrap_or_default();
+        files.push((PageUrl::from_normalized(url), preference, path));
     }
     Ok(())
 }
@@ -154,11 +928,14 @@ enum StoreEntryKind {
 
 impl StoreEntryKind {
     /// Classifies a directory entry for the store scan.
-    fn classify(path: &Path, file_type: &fs::FileType) -> Result<Self> {
-        let md_ext = Some(std::ffi::OsStr::new("md"));
+    fn classify(path: &Path, file_type: &fs::FileType, mapping: &PageMapping) -> Result<Self> {
+        let is_page_file = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| mapping.split_extension(name).is_some());
 
         if file_type.is_symlink() {
-            if path.extension() != md_ext {
+            if !is_page_file {
                 return Ok(Self::Skip);
             }
             let metadata = match fs::metadata(path) {
@@ -179,7 +956,7 @@ impl StoreEntryKind {
             return Ok(Self::Directory);
         }
 
-        Ok(if file_type.is_file() && path.extension() == md_ext {
+        Ok(if file_type.is_file() && is_page_file {
             Self::MarkdownFile
         } else {
             Self::Skip
@@ -206,6 +983,367 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flatpage_store_segment_policy() {
+        let root = TestDir::new();
+        write_page(root.path(), "о-нас.md", "# О нас");
+        // Decomposed, as some filesystems store names.
+        write_page(root.path(), "u\u{308}ber/index.md", "# Über");
+        write_page(root.path(), "install.md", "# Install");
+
+        let ascii = FlatPageStore::read_dir(root.path()).unwrap();
+        assert_eq!(ascii.iter().count(), 1);
+
+        let options = StoreOptions::new().segment_policy(SegmentPolicy::Unicode);
+        let mut store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+        assert_eq!(store.meta_by_url("/о-нас").unwrap().title, "О нас");
+        assert_eq!(store.meta_by_url("/über/").unwrap().title, "Über");
+        assert!(store.contains_url("/u\u{308}ber/"));
+        assert!(matches!(
+            store.lookup_request("/%C3%BCber/?x=1"),
+            Ok(Lookup::Found { url, .. }) if url.as_str() == "/über/"
+        ));
+        let page = store.page_by_url::<()>("/über/").unwrap().unwrap();
+        assert_eq!(page.title, "Über");
+
+        write_page(root.path(), "u\u{308}ber/index.md", "# Über uns");
+        store.refresh_url("/über/").unwrap();
+        assert_eq!(store.meta_by_url("/über/").unwrap().title, "Über uns");
+
+        let options = StoreOptions::new()
+            .segment_policy(SegmentPolicy::custom(|segment| segment != "install"));
+        let store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+        assert!(!store.contains_url("/install"));
+        assert!(store.contains_url("/о-нас"));
+    }
+
+    #[test]
+    fn flatpage_store_page_mapping() {
+        let root = TestDir::new();
+        write_page(root.path(), "README.md", "# Readme");
+        write_page(root.path(), "guides/README.md", "# Guides readme");
+        write_page(root.path(), "guides/index.md", "# Guides index");
+        write_page(root.path(), "guides/install.markdown", "# Install");
+        write_page(root.path(), "guides/notes.txt", "Notes");
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        assert!(store.contains_url("/README"));
+        assert!(!store.contains_url("/"));
+        assert!(!store.contains_url("/guides/install"));
+
+        let mapping = PageMapping::new()
+            .index_names(["index", "README"])
+            .extensions(["md", "markdown"]);
+        let options = StoreOptions::new().page_mapping(mapping);
+        let mut store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+        assert_eq!(store.iter().count(), 3);
+        assert_eq!(store.meta_by_url("/").unwrap().title, "Readme");
+        assert_eq!(store.meta_by_url("/guides/").unwrap().title, "Guides index");
+        assert_eq!(
+            store.meta_by_url("/guides/install").unwrap().title,
+            "Install"
+        );
+        let page = store.page_by_url::<()>("/guides/").unwrap().unwrap();
+        assert_eq!(page.title, "Guides index");
+
+        fs::remove_file(root.path().join("guides/index.md")).unwrap();
+        store.refresh_url("/guides/").unwrap();
+        assert_eq!(
+            store.meta_by_url("/guides/").unwrap().title,
+            "Guides readme"
+        );
+
+        write_page(root.path(), "index.md", "# Home");
+        store.refresh_url("/").unwrap();
+        assert_eq!(store.meta_by_url("/").unwrap().title, "Home");
+
+        store.refresh_url("/guides/README").unwrap();
+        store.refresh_url("/index").unwrap();
+        assert!(!store.contains_url("/guides/README"));
+        assert!(!store.contains_url("/index"));
+        assert_eq!(store.iter().count(), 3);
+
+        fs::remove_file(root.path().join("guides/README.md")).unwrap();
+        store.refresh_url("/guides/").unwrap();
+        assert!(!store.contains_url("/guides/"));
+    }
+
+    #[test]
+    fn flatpage_store_case_insensitive_lookup() {
+        let root = TestDir::new();
+        write_page(root.path(), "guides/index.md", "# Guides");
+        write_page(root.path(), "guides/install.md", "# Install");
+
+        let redirect = |store: &FlatPageStore, url| match store.lookup(url) {
+            Lookup::Redirect(url) => Some(url.to_string()),
+            _ => None,
+        };
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        assert_eq!(redirect(&store, "/Guides/Install"), None);
+
+        let options = StoreOptions::new().case_insensitive(true);
+        let mut store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+        assert!(matches!(
+            store.lookup("/guides/install"),
+            Lookup::Found { .. }
+        ));
+        assert_eq!(
+            redirect(&store, "/Guides/Install").as_deref(),
+            Some("/guides/install")
+        );
+        assert_eq!(redirect(&store, "/GUIDES").as_deref(), Some("/guides/"));
+        assert!(store.meta_by_url("/Guides/Install").is_none());
+
+        write_page(root.path(), "guides/Install.md", "# Install again");
+        assert!(matches!(
+            store.refresh_url("/guides/Install"),
+            Err(Error::CaseConflict { first, second })
+                if first.ends_with("guides/install.md") && second.ends_with("guides/Install.md")
+        ));
+        assert!(matches!(
+            FlatPageStore::read_dir_with(root.path(), &options),
+            Err(Error::CaseConflict { first, .. }) if first.ends_with("guides/Install.md")
+        ));
+
+        std::fs::remove_file(root.path().join("guides/install.md")).unwrap();
+        store.refresh_url("/guides/install").unwrap();
+        store.refresh_url("/guides/Install").unwrap();
+        assert_eq!(
+            redirect(&store, "/guides/install").as_deref(),
+            Some("/guides/Install")
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn flatpage_store_frontmatter_redirects() {
+        let root = TestDir::new();
+        write_page(
+            root.path(),
+            "guides/install.md",
+            "---\naliases: [/install, setup]\n---\n# Install",
+        );
+        write_page(
+            root.path(),
+            "old.md",
+            "---\nredirect_to: /guides/install\n---\n",
+        );
+        write_page(
+            root.path(),
+            "older.md",
+            "---\nredirect_from: /oldest\nredirect_to: old\n---\n",
+        );
+
+        let redirect = |store: &FlatPageStore, url| match store.lookup(url) {
+            Lookup::Redirect(url) => Some(url.to_string()),
+            _ => None,
+        };
+        let mut store = FlatPageStore::read_dir(root.path()).unwrap();
+        for url in ["/install", "/guides/setup", "/old"] {
+            assert_eq!(redirect(&store, url).as_deref(), Some("/guides/install"));
+        }
+        assert_eq!(redirect(&store, "/older").as_deref(), Some("/old"));
+        assert_eq!(redirect(&store, "/oldest").as_deref(), Some("/old"));
+        assert!(!store.contains_url("/old"));
+        assert!(store.page_by_url::<()>("/old").unwrap().is_none());
+        assert!(store.check_redirects().is_empty());
+
+        write_page(root.path(), "guides/install.md", "# Install");
+        store.refresh_url("/guides/install").unwrap();
+        assert_eq!(redirect(&store, "/install"), None);
+
+        write_page(root.path(), "guides/setup.md", "# Setup");
+        store.refresh_url("/guides/setup").unwrap();
+        write_page(
+            root.path(),
+            "guides/install.md",
+            "---\naliases: [setup]\n---\n# Install",
+        );
+        assert!(matches!(
+            store.refresh_url("/guides/install"),
+            Err(Error::RedirectConflict { url, first, second })
+                if url == "/guides/setup"
+                    && first.ends_with("guides/setup.md")
+                    && second.ends_with("guides/install.md")
+        ));
+        assert!(matches!(
+            FlatPageStore::read_dir(root.path()),
+            Err(Error::RedirectConflict { .. })
+        ));
+
+        write_page(
+            root.path(),
+            "guides/install.md",
+            "---\naliases: /oldest\n---\n",
+        );
+        assert!(matches!(
+            FlatPageStore::read_dir(root.path()),
+            Err(Error::RedirectConflict { url, first, second })
+                if url == "/oldest"
+                    && first.ends_with("guides/install.md")
+                    && second.ends_with("older.md")
+        ));
+
+        write_page(
+            root.path(),
+            "guides/install.md",
+            "---\naliases: /docs\nredirect_to: https://example.com/install\n---\n",
+        );
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        for url in ["/guides/install", "/docs"] {
+            assert!(matches!(
+                store.lookup(url),
+                Lookup::Rule { location, status: 301 } if location == "https://example.com/install"
+            ));
+        }
+        assert!(!store.contains_url("/guides/install"));
+        assert!(store.check_redirects().is_empty());
+
+        write_page(
+            root.path(),
+            "guides/install.md",
+            "---\naliases: [../up]\n---\n",
+        );
+        assert!(matches!(
+            FlatPageStore::read_dir(root.path()),
+            Err(Error::InvalidRedirect { url, .. }) if url == "../up"
+        ));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn flatpage_store_failed_refresh_keeps_redirects() {
+        let root = TestDir::new();
+        write_page(root.path(), "setup.md", "# Setup");
+        write_page(
+            root.path(),
+            "install.md",
+            "---\naliases: [/old, /older]\n---\n# Install",
+        );
+        let options = StoreOptions::new().case_insensitive(true);
+        let mut store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+
+        let redirect = |store: &FlatPageStore, url| match store.lookup(url) {
+            Lookup::Redirect(url) => Some(url.to_string()),
+            _ => None,
+        };
+        for content in [
+            "---\naliases: [/new, /setup]\n---\n# Moved",
+            "---\naliases: [/new, ../up]\n---\n# Moved",
+            "---\naliases: /new\nredirect_to: ../up\n---\n",
+        ] {
+            write_page(root.path(), "install.md", content);
+            assert!(store.refresh_url("/install").is_err());
+            assert_eq!(store.meta_by_url("/install").unwrap().title, "Install");
+            assert_eq!(redirect(&store, "/OLD").as_deref(), Some("/install"));
+            assert_eq!(redirect(&store, "/new"), None);
+        }
+
+        write_page(root.path(), "Setup.md", "# Setup again");
+        std::fs::remove_file(root.path().join("setup.md")).unwrap();
+        write_page(
+            root.path(),
+            "install.md",
+            "---\naliases: /old\n---\n# Install",
+        );
+        store.refresh_url("/install").unwrap();
+        assert!(matches!(
+            store.refresh_url("/Setup"),
+            Err(Error::CaseConflict { .. })
+        ));
+        assert!(!store.contains_url("/Setup"));
+        assert_eq!(redirect(&store, "/older"), None);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn flatpage_store_case_insensitive_redirects() {
+        let root = TestDir::new();
+        write_page(
+            root.path(),
+            "guides/install.md",
+            "---\naliases: /Old\n---\n# Install",
+        );
+
+        let redirect = |store: &FlatPageStore, url| match store.lookup(url) {
+            Lookup::Redirect(url) => Some(url.to_string()),
+            _ => None,
+        };
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        assert_eq!(redirect(&store, "/old"), None);
+        assert_eq!(
+            redirect(&store, "/Old/").as_deref(),
+            Some("/guides/install")
+        );
+
+        let options = StoreOptions::new().case_insensitive(true);
+        let mut store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+        for url in ["/Old", "/old", "/Old/", "/OLD/"] {
+            assert_eq!(redirect(&store, url).as_deref(), Some("/guides/install"));
+        }
+
+        write_page(root.path(), "guides/install.md", "# Install");
+        store.refresh_url("/guides/install").unwrap();
+        assert!(matches!(store.lookup("/old"), Lookup::NotFound));
+    }
+
+    #[test]
+    fn flatpage_store_redirects_file() {
+        let root = TestDir::new();
+        write_page(root.path(), "news/index.md", "# News");
+        write_page(root.path(), "news/first.md", "# First");
+        write_page(root.path(), "logo.svg", "<svg/>");
+        let redirects = root.path().join("_redirects");
+        std::fs::write(
+            &redirects,
+            "/news/first /elsewhere\n/blog/* /news/:splat 302\n/about https://example.com/\n/missing /gone\n/logo /logo.svg\n",
+        )
+        .unwrap();
+
+        let rule = |store: &FlatPageStore, url| match store.lookup(url) {
+            Lookup::Rule { location, status } => Some((location, status)),
+            _ => None,
+        };
+        let mut store = FlatPageStore::read_dir(root.path()).unwrap();
+        assert!(matches!(store.lookup("/news/first"), Lookup::Found { .. }));
+        assert_eq!(
+            rule(&store, "/blog/first"),
+            Some(("/news/first".to_string(), 302))
+        );
+        assert_eq!(
+            rule(&store, "/about"),
+            Some(("https://example.com/".to_string(), 301))
+        );
+        assert_eq!(rule(&store, "/blog"), None);
+
+        assert_eq!(
+            store.check_redirects(),
+            vec![
+                BrokenRedirect {
+                    from: "/news/first".into(),
+                    to: "/elsewhere".into(),
+                    path: redirects.clone(),
+                    line: Some(1),
+                },
+                BrokenRedirect {
+                    from: "/missing".into(),
+                    to: "/gone".into(),
+                    path: redirects.clone(),
+                    line: Some(4),
+                },
+            ]
+        );
+
+        std::fs::write(&redirects, "/blog/* /news/:splat 418\n").unwrap();
+        assert!(matches!(
+            store.refresh_redirects(),
+            Err(Error::InvalidRedirectRule { line: 1, .. })
+        ));
+        std::fs::remove_file(&redirects).unwrap();
+        store.refresh_redirects().unwrap();
+        assert_eq!(rule(&store, "/blog/first"), None);
+    }
+
     #[test]
     fn flatpage_store_reads_nested_paths() {
         let root = TestDir::new();
@@ -255,6 +1393,220 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flatpage_store_title_text_and_html() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# *Home* &amp; more");
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        let meta = store.meta_by_url("/").unwrap();
+        assert_eq!(meta.title_text(), "Home & more");
+        assert_eq!(meta.title_html(), "<em>Home</em> &amp; more");
+    }
+
+    #[test]
+    fn flatpage_store_caches_summaries() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# Home\n\nWelcome *home*\n\nMore");
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        assert_eq!(store.meta_by_url("/").unwrap().summary, None);
+
+        let store = FlatPageStore::read_dir_with(root.path(), &StoreOptions::new().summaries(true))
+            .unwrap();
+        let meta = store.meta_by_url("/").unwrap();
+        assert_eq!(meta.summary.as_deref(), Some("Welcome home"));
+        assert_eq!(meta.meta_description(), Some("Welcome home"));
+    }
+
+    #[test]
+    fn flatpage_store_caches_stats() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# Home\n\nWelcome home");
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        assert_eq!(store.meta_by_url("/").unwrap().stats, None);
+
+        let store =
+            FlatPageStore::read_dir_with(root.path(), &StoreOptions::new().stats(true)).unwrap();
+        let stats = store.meta_by_url("/").unwrap().stats.unwrap();
+        assert_eq!(stats.word_count, 3);
+        assert_eq!(stats.heading_count, 1);
+    }
+
+    #[test]
+    fn flatpage_store_accepts_page_urls() {
+        let (_root, store) = nav_store();
+        let url = PageUrl::parse("/guides/install").unwrap();
+        assert_eq!(store.meta_by_url(&url).unwrap().title, "Install");
+        assert!(store.page_by_url::<()>(&url).unwrap().is_some());
+        assert!(store.contains_url(url.parent().unwrap()));
+
+        let mut urls = store
+            .pages()
+            .map(|(url, _)| url.clone())
+            .collect::<Vec<_>>();
+        urls.sort();
+        assert_eq!(urls[0], PageUrl::parse("/").unwrap());
+        assert_eq!(urls.len(), store.iter().count());
+    }
+
+    #[test]
+    fn flatpage_store_lookup_redirects_trailing_slash() {
+        let (_root, store) = nav_store();
+        let url = |url: &str| PageUrl::parse(url).unwrap();
+
+        assert!(matches!(
+            store.lookup("/guides/install"),
+            Lookup::Found { url: found, .. } if *found == url("/guides/install")
+        ));
+        assert_eq!(store.lookup("/guides/").found().unwrap().title, "*Guides*");
+        assert!(matches!(
+            store.lookup("/guides"),
+            Lookup::Redirect(canonical) if canonical == url("/guides/")
+        ));
+        assert!(matches!(
+            store.lookup("/guides/install/"),
+            Lookup::Redirect(canonical) if canonical == url("/guides/install")
+        ));
+        assert!(matches!(store.lookup("/api/"), Lookup::NotFound));
+        assert!(matches!(store.lookup("/missing"), Lookup::NotFound));
+        assert!(matches!(store.lookup("invalid"), Lookup::NotFound));
+
+        assert!(store.meta_by_url("/guides").is_none());
+    }
+
+    #[test]
+    fn flatpage_store_lookup_request() {
+        let (_root, store) = nav_store();
+        assert!(matches!(
+            store.lookup_request("/guides/install?ref=nav#setup"),
+            Ok(Lookup::Found { .. })
+        ));
+        assert!(matches!(
+            store.lookup_request("/guides?ref=nav"),
+            Ok(Lookup::Redirect(_))
+        ));
+        assert!(matches!(
+            store.lookup_request("/missing"),
+            Ok(Lookup::NotFound)
+        ));
+        assert!(matches!(
+            store.lookup_request("/%2e%2e/secret"),
+            Err(Error::InvalidUrl { .. })
+        ));
+    }
+
+    #[test]
+    fn flatpage_store_caches_validators() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# Home");
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        assert_eq!(store.meta_by_url("/").unwrap().validators, None);
+
+        let mut store =
+            FlatPageStore::read_dir_with(root.path(), &StoreOptions::new().cache_validators(true))
+                .unwrap();
+        let page = store.page_by_url::<()>("/").unwrap().unwrap();
+        let validators = store.meta_by_url("/").unwrap().validators.unwrap();
+        assert_eq!(validators, page.validators);
+        assert!(validators.modified.is_some());
+        assert!(validators.is_not_modified(Some(&page.validators.etag()), None));
+
+        write_page(root.path(), "index.md", "# New home");
+        store.refresh_url("/").unwrap();
+        let refreshed = store.meta_by_url("/").unwrap().validators.unwrap();
+        assert!(!refreshed.is_not_modified(Some(&validators.etag()), None));
+    }
+
+    #[test]
+    fn flatpage_store_search() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# Home\n\nSee how to install it.");
+        write_page(root.path(), "install.md", "# Install\n\nRun the script.");
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        assert!(store.search("install").is_empty());
+
+        let mut store =
+            FlatPageStore::read_dir_with(root.path(), &StoreOptions::new().search_index(true))
+                .unwrap();
+        let urls = |store: &FlatPageStore, query| {
+            store
+                .search(query)
+                .into_iter()
+                .map(|hit| hit.url)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(urls(&store, "install"), vec!["/install", "/"]);
+
+        write_page(root.path(), "install.md", "# Setup\n\nRun the script.");
+        write_page(root.path(), "guide.md", "# Install guide");
+        store.refresh_url("/install").unwrap();
+        store.refresh_url("/guide").unwrap();
+        assert_eq!(urls(&store, "install"), vec!["/guide", "/"]);
+        assert_eq!(store.meta_by_url("/install").unwrap().title, "Setup");
+
+        std::fs::remove_file(root.path().join("guide.md")).unwrap();
+        store.refresh_url("/guide").unwrap();
+        assert_eq!(urls(&store, "install"), vec!["/"]);
+        assert!(!store.contains_url("/guide"));
+    }
+
+    #[test]
+    fn flatpage_store_client_search_index() {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# *Home*\n\nThe home page");
+        write_page(root.path(), "install.md", "# Install\n\nInstall the home");
+
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        let index = store.client_search_index(&Tokenizer::english()).unwrap();
+        assert_eq!(
+            serde_json::to_string(&index).unwrap(),
+            r#"{"pages":[{"url":"/","title":"Home"},{"url":"/install","title":"Install"}],"terms":{"home":[[0,[0,2]],[1,[3]]],"install":[[1,[0,1]]],"page":[[0,[3]]]}}"#
+        );
+    }
+
+    #[test]
+    fn flatpage_store_client_search_index_with_page_mapping() {
+        let root = TestDir::new();
+        write_page(root.path(), "README.md", "# Home");
+        write_page(root.path(), "guides/install.markdown", "# Install");
+        write_page(root.path(), "u\u{308}ber.md", "# Team");
+
+        let mapping = PageMapping::new()
+            .index_names(["index", "README"])
+            .extensions(["md", "markdown"])
+            .segment_policy(SegmentPolicy::Unicode);
+        let options = StoreOptions::new().page_mapping(mapping);
+        let store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+        let index = store.client_search_index(&Tokenizer::english()).unwrap();
+        let urls = index
+            .pages
+            .iter()
+            .map(|page| page.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(urls, ["/", "/guides/install", "/\u{fc}ber"]);
+        assert_eq!(index.terms["team"], [(2, vec![0])]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn flatpage_store_refresh_ignores_symlinked_directories() {
+        use std::os::unix::fs::symlink;
+
+        let root = TestDir::new();
+        let external = TestDir::new();
+        write_page(root.path(), "index.md", "# Home");
+        write_page(external.path(), "secret.md", "# Secret");
+        symlink(external.path(), root.path().join("linked")).unwrap();
+
+        let mut store = FlatPageStore::read_dir(root.path()).unwrap();
+        store.refresh_url("/linked/secret").unwrap();
+        assert!(!store.contains_url("/linked/secret"));
+    }
+
     #[test]
     fn flatpage_store_reports_read_dir_error() {
         let root = TestDir::new();
@@ -340,4 +1692,134 @@ mod tests {
     fn flatpage_store_reports_yaml_frontmatter_errors() {
         assert_read_dir_reports_parse_frontmatter_error("---\ntitle: [\n---\n# Foo");
     }
+
+    fn nav_store() -> (TestDir, FlatPageStore) {
+        let root = TestDir::new();
+        write_page(root.path(), "index.md", "# Home");
+        write_page(root.path(), "guides/index.md", "# *Guides*");
+        write_page(root.path(), "guides/install.md", "# Install");
+        write_page(root.path(), "guides/advanced/index.md", "# Advanced");
+        write_page(root.path(), "guides/advanced/tuning.md", "# Tuning");
+        write_page(root.path(), "api/reference.md", "# Reference");
+        let store = FlatPageStore::read_dir(root.path()).unwrap();
+        (root, store)
+    }
+
+    #[test]
+    fn flatpage_store_suggest() {
+        let (_root, store) = nav_store();
+        let suggest = |url: &str, n| {
+            store
+                .suggest(url, n)
+                .into_iter()
+                .map(|link| link.url)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(suggest("/guides/instal", 1), vec!["/guides/install"]);
+        assert_eq!(suggest("/docs/install?x=1", 1), vec!["/guides/install"]);
+        assert_eq!(suggest("/tuning", 1), vec!["/guides/advanced/tuning"]);
+        assert_eq!(suggest("/api/refrence/", 1), vec!["/api/reference"]);
+        assert_eq!(suggest("/guides/instal", 0), Vec::<String>::new());
+        assert_eq!(suggest("/xyzzy-qwerty", 5), Vec::<String>::new());
+        assert!(suggest("/guides/advanced/tunning", 5).len() > 1);
+
+        // Only the start of long URLs is compared.
+        for long in [
+            format!("/guides/instal{}", "/x".repeat(100_000)),
+            format!("/guides/instal{}", "l".repeat(100_000)),
+        ] {
+            assert_eq!(
+                suggest(&long[..300], 5),
+                suggest(&long[..long.len() - 1], 5)
+            );
+        }
+    }
+
+    #[test]
+    fn flatpage_store_navigation() {
+        let (_root, store) = nav_store();
+        let urls = |links: Vec<NavLink>| links.into_iter().map(|link| link.url).collect::<Vec<_>>();
+
+        assert_eq!(
+            store.breadcrumbs("/guides/install"),
+            vec![
+                NavLink {
+                    url: "/".into(),
+                    title: "Home".into()
+                },
+                NavLink {
+                    url: "/guides/".into(),
+                    title: "Guides".into()
+                },
+                NavLink {
+                    url: "/guides/install".into(),
+                    title: "Install".into()
+                },
+            ]
+        );
+        assert_eq!(
+            urls(store.breadcrumbs("/guides/advanced/")),
+            vec!["/", "/guides/", "/guides/advanced/"]
+        );
+        assert_eq!(
+            urls(store.breadcrumbs("/api/reference")),
+            vec!["/", "/api/reference"]
+        );
+        assert!(store.breadcrumbs("invalid").is_empty());
+
+        assert_eq!(urls(store.children("/")), vec!["/guides/"]);
+        assert_eq!(
+            urls(store.children("/guides/")),
+            vec!["/guides/advanced/", "/guides/install"]
+        );
+        assert!(store.children("/guides/install").is_empty());
+    }
+
+    #[test]
+    fn flatpage_store_serializes_page_context() {
+        let (_root, store) = nav_store();
+        let context = store.page_context::<()>("/guides/").unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(&context).unwrap(),
+            serde_json::json!({
+                "url": "/guides/",
+                "page": {
+                    "title": "*Guides*",
+                    "description": null,
+                    "body": "# *Guides*",
+                    "extra": null,
+                },
+                "meta": {
+                    "title": "*Guides*",
+                    "description": null,
+                    "summary": null,
+                    "stats": null,
+                },
+                "content": "<h1><em>Guides</em></h1>\n",
+                "toc": [{"level": 1, "id": null, "text": "Guides"}],
+                "breadcrumbs": [
+                    {"url": "/", "title": "Home"},
+                    {"url": "/guides/", "title": "Guides"},
+                ],
+                "children": [
+                    {"url": "/guides/advanced/", "title": "Advanced"},
+                    {"url": "/guides/install", "title": "Install"},
+                ],
+            })
+        );
+        assert!(store.page_context::<()>("/missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn flatpage_store_page_context_uses_canonical_url() {
+        let root = TestDir::new();
+        write_page(root.path(), "\u{fc}ber.md", "# About");
+        let options = StoreOptions::new().segment_policy(SegmentPolicy::Unicode);
+        let store = FlatPageStore::read_dir_with(root.path(), &options).unwrap();
+
+        let context = store.page_context::<()>("/u\u{308}ber").unwrap().unwrap();
+        assert_eq!(context.url, "/\u{fc}ber");
+        assert_eq!(context.breadcrumbs.last().unwrap().url, "/\u{fc}ber");
+    }
 }
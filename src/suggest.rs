@@ -0,0 +1,179 @@
+//! "Did you mean" suggestions for missing pages.
+
+use crate::{FlatPageMeta, PageUrl, tokenizer::words, util::percent_decode};
+
+/// Minimal similarity of a suggested page, from 0 to 1.
+const MIN_SIMILARITY: f64 = 0.4;
+/// Weight of the title similarity, the URL similarity gets the rest.
+const TITLE_WEIGHT: f64 = 0.3;
+/// Characters of the requested path that are compared, the rest is ignored
+/// to bound the work per request.
+const MAX_PATH_CHARS: usize = 256;
+/// Requested segments that are compared.
+const MAX_SEGMENTS: usize = 16;
+/// Characters of a segment that are compared.
+const MAX_SEGMENT_CHARS: usize = 64;
+
+/// Returns up to `n` pages most similar to a requested URL, best first, see
+/// [`FlatPageStore::suggest`].
+///
+/// [`FlatPageStore::suggest`]: crate::FlatPageStore::suggest
+pub(crate) fn suggest<'a>(
+    url: &str,
+    pages: impl Iterator<Item = (&'a PageUrl, &'a FlatPageMeta)>,
+    n: usize,
+) -> Vec<&'a PageUrl> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let path = path
+        .char_indices()
+        .nth(MAX_PATH_CHARS)
+        .map_or(path, |(end, _)| &path[..end]);
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .take(MAX_SEGMENTS)
+        .map(|segment| comparable(&percent_decode(segment).unwrap_or_else(|| segment.to_string())))
+        .collect::<Vec<_>>();
+    let query_words = segments
+        .last()
+        .map(|segment| lowercase_words(segment))
+        .unwrap_or_default();
+
+    let mut scored = pages
+        .filter_map(|(page_url, meta)| {
+            let page_segments = page_url.segments().map(comparable).collect::<Vec<_>>();
+            let url_similarity = 1.0 - segments_distance(&segments, &page_segments);
+            let title_similarity =
+                words_similarity(&query_words, &lowercase_words(&meta.title_text()));
+            let similarity =
+                (1.0 - TITLE_WEIGHT) * url_similarity + TITLE_WEIGHT * title_similarity;
+            (similarity >= MIN_SIMILARITY).then_some((similarity, page_url))
+        })
+        .collect::<Vec<_>>();
+    scored.sort_by(|(a_score, a_url), (b_score, b_url)| {
+        b_score.total_cmp(a_score).then_with(|| a_url.cmp(b_url))
+    });
+    scored.into_iter().take(n).map(|(_, url)| url).collect()
+}
+
+/// Returns the lowercase start of a segment that is compared.
+fn comparable(segment: &str) -> String {
+    segment
+        .chars()
+        .take(MAX_SEGMENT_CHARS)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Returns the lowercase words of a text.
+fn lowercase_words(text: &str) -> Vec<String> {
+    words(text).map(|(_, word)| word.to_lowercase()).collect()
+}
+
+/// Edit distance between two segment lists, from 0 to 1.
+///
+/// Inserting or removing a segment costs 1, replacing one costs the
+/// normalized edit distance of the two segments.
+fn segments_distance(a: &[String], b: &[String]) -> f64 {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    let mut previous = (0..=b.len()).map(|j| j as f64).collect::<Vec<_>>();
+    for (i, a_segment) in a.iter().enumerate() {
+        let mut current = vec![(i + 1) as f64];
+        for (j, b_segment) in b.iter().enumerate() {
+            let replace = previous[j] + text_distance(a_segment, b_segment);
+            current.push(replace.min(previous[j + 1] + 1.0).min(current[j] + 1.0));
+        }
+        previous = current;
+    }
+    previous[b.len()] / longest as f64
+}
+
+/// Levenshtein distance between two texts, normalized to 0..=1 by the length
+/// of the longer one.
+fn text_distance(a: &str, b: &str) -> f64 {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a_char != b_char);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()] as f64 / longest as f64
+}
+
+/// Average over the query words of their best similarity to a title word,
+/// from 0 to 1.
+fn words_similarity(query: &[String], title: &[String]) -> f64 {
+    if query.is_empty() || title.is_empty() {
+        return 0.0;
+    }
+    let total = query
+        .iter()
+        .map(|word| {
+            title
+                .iter()
+                .map(|title_word| 1.0 - text_distance(word, title_word))
+                .fold(0.0, f64::max)
+        })
+        .sum::<f64>();
+    total / query.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_distance() {
+        assert_eq!(text_distance("install", "install"), 0.0);
+        assert_eq!(text_distance("instal", "install"), 1.0 / 7.0);
+        assert_eq!(text_distance("abc", ""), 1.0);
+        assert_eq!(text_distance("", ""), 0.0);
+    }
+
+    #[test]
+    fn test_comparable() {
+        assert_eq!(comparable("Install"), "install");
+        assert_eq!(comparable(&"Ab".repeat(100)).len(), MAX_SEGMENT_CHARS);
+    }
+
+    #[test]
+    fn test_segments_distance() {
+        let segments = |url: &str| url.split('/').map(String::from).collect::<Vec<_>>();
+        assert_eq!(
+            segments_distance(&segments("guides/install"), &segments("guides/install")),
+            0.0
+        );
+        assert_eq!(
+            segments_distance(&segments("install"), &segments("guides/install")),
+            0.5
+        );
+        assert_eq!(
+            segments_distance(&segments("docs/install"), &segments("guides/install")),
+            text_distance("docs", "guides") / 2.0
+        );
+        assert_eq!(segments_distance(&[], &segments("install")), 1.0);
+    }
+
+    #[test]
+    fn test_words_similarity() {
+        let words = |text| lowercase_words(text);
+        assert_eq!(
+            words_similarity(&words("getting-started"), &words("Getting Started")),
+            1.0
+        );
+        assert!(words_similarity(&words("tuning"), &words("Install")) < MIN_SIMILARITY);
+        assert_eq!(words_similarity(&[], &words("Install")), 0.0);
+    }
+}
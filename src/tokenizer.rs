@@ -0,0 +1,124 @@
+//! Word splitting and stop-word filtering for search.
+
+use std::collections::HashSet;
+
+/// Common English words that carry little meaning for search.
+const ENGLISH_STOP_WORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "do", "does", "for", "from", "had", "has", "have", "he", "her", "his",
+    "how", "i", "if", "in", "into", "is", "it", "its", "just", "me", "more", "my", "no", "not",
+    "of", "on", "or", "our", "out", "she", "so", "than", "that", "the", "their", "them", "then",
+    "there", "these", "they", "this", "to", "up", "was", "we", "were", "what", "when", "which",
+    "who", "will", "with", "would", "you", "your",
+];
+
+/// Splits text into lowercase search terms
+///
+/// Words are runs of alphanumeric characters in any script. Words shorter
+/// than the minimum length and stop words are skipped.
+#[derive(Clone, Debug)]
+pub struct Tokenizer {
+    /// Words that are never turned into terms.
+    stop_words: HashSet<String>,
+    /// Minimum number of characters in a term.
+    min_length: usize,
+}
+
+impl Default for Tokenizer {
+    /// Creates a tokenizer without stop words, keeping every word.
+    fn default() -> Self {
+        Self {
+            stop_words: HashSet::new(),
+            min_length: 1,
+        }
+    }
+}
+
+impl Tokenizer {
+    /// Creates a tokenizer without stop words, keeping every word.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a tokenizer skipping common English stop words.
+    pub fn english() -> Self {
+        Self::new().stop_words(ENGLISH_STOP_WORDS.iter().copied())
+    }
+
+    /// Adds stop words, such as a list for the content language.
+    ///
+    /// Stop words are matched case-insensitively.
+    pub fn stop_words<S: AsRef<str>>(mut self, words: impl IntoIterator<Item = S>) -> Self {
+        self.stop_words
+            .extend(words.into_iter().map(|word| word.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Skips words shorter than the given number of characters.
+    pub fn min_length(mut self, min_length: usize) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    /// Returns the terms of a text with their word positions.
+    ///
+    /// Positions count every word, including skipped ones, so the distance
+    /// between terms matches the text.
+    pub fn tokenize<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (usize, String)> + 'a {
+        words(text)
+            .enumerate()
+            .filter_map(|(position, (_, word))| Some((position, self.term(word)?)))
+    }
+
+    /// Returns the term for a single word, or `None` if the word is skipped.
+    pub(crate) fn term(&self, word: &str) -> Option<String> {
+        let term = word.to_lowercase();
+        (term.chars().count() >= self.min_length && !self.stop_words.contains(&term))
+            .then_some(term)
+    }
+}
+
+/// Iterates over alphanumeric words with their byte offsets.
+pub(crate) fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let terms = |tokenizer: &Tokenizer, text| {
+            tokenizer
+                .tokenize(text)
+                .map(|(position, term)| format!("{position}:{term}"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            terms(&Tokenizer::new(), "The Über-fast, fox!"),
+            vec!["0:the", "1:über", "2:fast", "3:fox"]
+        );
+        assert_eq!(
+            terms(&Tokenizer::english(), "The Über-fast, fox!"),
+            vec!["1:über", "2:fast", "3:fox"]
+        );
+        assert_eq!(
+            terms(
+                &Tokenizer::new().stop_words(["Und"]).min_length(4),
+                "Katze und Hund"
+            ),
+            vec!["0:katze", "2:hund"]
+        );
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            words("Привет, мир 42").collect::<Vec<_>>(),
+            vec![(0, "Привет"), (14, "мир"), (21, "42")]
+        );
+    }
+}
@@ -0,0 +1,477 @@
+//! Public validated page URLs.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use crate::{
+    Error, PageMapping, Result, SegmentPolicy,
+    error::{PathProblem, UrlProblem},
+    util::{AbsPagePath, NormalizedUrl, RelPagePath, percent_decode},
+};
+
+/// A validated page URL, such as `/`, `/guides/` or `/guides/install`
+///
+/// URLs start with a slash, and segments follow the
+/// [folder structure](crate#folder-structure) rules. A trailing slash marks a
+/// folder index page: `/guides/` is `guides/index.md`, while `/guides` is
+/// `guides.md`.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PageUrl(NormalizedUrl<'static>);
+
+impl PageUrl {
+    /// Validates a page URL.
+    pub fn parse(url: &str) -> Result<Self> {
+        Self::parse_with(url, &SegmentPolicy::Ascii)
+    }
+
+    /// Normalizes and validates a page URL with a segment policy.
+    pub fn parse_with(url: &str, policy: &SegmentPolicy) -> Result<Self> {
+        NormalizedUrl::parse(url, policy)
+            .map(|url| Self(url.into_owned()))
+            .map_err(|problem| Error::invalid_url(url, problem))
+    }
+
+    /// Validates the path of a request URI, such as
+    /// `/guides/install?ref=nav#setup`.
+    ///
+    /// The query string and fragment are dropped, and each segment is
+    /// percent-decoded before validation. An encoded slash (`%2F`) inside a
+    /// segment is rejected rather than treated as a separator.
+    pub fn from_request_uri(uri: &str) -> Result<Self> {
+        Self::from_request_uri_with(uri, &SegmentPolicy::Ascii)
+    }
+
+    /// Validates the path of a request URI with a segment policy, see
+    /// [`PageUrl::from_request_uri`].
+    pub fn from_request_uri_with(uri: &str, policy: &SegmentPolicy) -> Result<Self> {
+        let path = uri.split(['?', '#']).next().unwrap_or_default();
+        let decoded = path
+            .split('/')
+            .map(percent_decode)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| Error::invalid_url(uri, UrlProblem::InvalidPercentEncoding))?;
+        if let Some(segment) = decoded.iter().find(|segment| segment.contains('/')) {
+            return Err(Error::invalid_url(
+                uri,
+                UrlProblem::InvalidSegment(segment.clone()),
+            ));
+        }
+        NormalizedUrl::parse(&decoded.join("/"), policy)
+            .map(|url| Self(url.into_owned()))
+            .map_err(|problem| Error::invalid_url(uri, problem))
+    }
+
+    /// Wraps an already validated URL.
+    pub(crate) fn from_normalized(url: NormalizedUrl<'_>) -> Self {
+        Self(url.into_owned())
+    }
+
+    /// Returns the internal URL representation.
+    pub(crate) fn normalized(&self) -> &NormalizedUrl<'static> {
+        &self.0
+    }
+
+    /// Returns the URL as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.0.as_ref()
+    }
+
+    /// Iterates over the segments, such as `guides` and `install` for
+    /// `/guides/install`.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.as_str()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+    }
+
+    /// Returns whether the URL is `/` or a folder index with a trailing slash.
+    pub fn is_index(&self) -> bool {
+        self.as_str().ends_with('/')
+    }
+
+    /// Returns the folder index containing the page, such as `/guides/` for
+    /// both `/guides/install` and `/guides/advanced/`, or `None` for `/`.
+    pub fn parent(&self) -> Option<Self> {
+        self.0.parent().map(Self::from_normalized)
+    }
+
+    /// Appends a relative path, such as `install` or `advanced/`, to the
+    /// folder of the URL.
+    ///
+    /// `/guides/`.join("install") and `/guides/setup`.join("install") both
+    /// give `/guides/install`. Unlike relative links in a browser, the path
+    /// can only go down: `..`, `.` and absolute paths such as `/about` are
+    /// rejected as invalid URLs.
+    pub fn join(&self, path: &str) -> Result<Self> {
+        self.join_with(path, &SegmentPolicy::Ascii)
+    }
+
+    /// Resolves a relative path with a segment policy, see [`PageUrl::join`].
+    pub fn join_with(&self, path: &str, policy: &SegmentPolicy) -> Result<Self> {
+        let folder = if self.is_index() {
+            self.as_str()
+        } else {
+            let url = self.as_str();
+            &url[..=url.rfind('/').unwrap_or_default()]
+        };
+        Self::parse_with(&format!("{folder}{path}"), policy)
+    }
+
+    /// Returns the Markdown file path relative to the store root, such as
+    /// `guides/index.md` for `/guides/`.
+    pub fn to_rel_path(&self) -> PathBuf {
+        RelPagePath::from(&self.0).as_ref().to_path_buf()
+    }
+}
+
+/// Returns the URL of a page file.
+///
+/// A relative path is relative to the pages root, such as `guides/index.md`.
+/// An absolute path, such as one reported by a file watcher, has to be inside
+/// the root, which is made absolute against the current directory for the
+/// comparison. Symlinks aren't resolved, and the file doesn't have to exist.
+///
+/// ```
+/// let url = flatpage::url_for_path("./pages", "guides/index.md").unwrap();
+/// assert_eq!(url.as_str(), "/guides/");
+/// ```
+pub fn url_for_path(root: impl AsRef<Path>, path: impl AsRef<Path>) -> Result<PageUrl> {
+    url_for_path_with(root, path, &PageMapping::default())
+}
+
+/// Returns the URL of a page file with a page mapping, see [`url_for_path`].
+pub fn url_for_path_with(
+    root: impl AsRef<Path>,
+    path: impl AsRef<Path>,
+    mapping: &PageMapping,
+) -> Result<PageUrl> {
+    let path = path.as_ref();
+    let relative_path = if path.is_absolute() {
+        let root = root.as_ref();
+        let root = std::path::absolute(root).map_err(|e| Error::read_metadata(e, root))?;
+        path.strip_prefix(&root)
+            .map_err(|_| Error::invalid_path(path, PathProblem::OutsideRoot))?
+    } else {
+        path
+    };
+    let url = NormalizedUrl::from_path(relative_path, mapping)
+        .map_err(|problem| Error::invalid_path(path, problem))?;
+    Ok(PageUrl(url))
+}
+
+/// Returns the Markdown file path of a page URL under the pages root.
+///
+/// ```
+/// # use std::path::Path;
+/// let path = flatpage::path_for_url("./pages", "/guides/install").unwrap();
+/// assert_eq!(path, Path::new("./pages/guides/install.md"));
+/// ```
+pub fn path_for_url(root: impl AsRef<Path>, url: impl AsRef<str>) -> Result<PathBuf> {
+    path_for_url_with(root, url, &PageMapping::default())
+}
+
+/// Returns the Markdown file path of a page URL with a page mapping, see
+/// [`path_for_url`].
+///
+/// When several files may hold the page, such as `guides/index.md` and
+/// `guides/README.md`, the most preferred existing one is returned, or the
+/// most preferred one if none exists. URLs naming an index file, such as
+/// `/guides/index`, fail with [`UrlProblem::Unmapped`], as the page is served
+/// at the folder URL.
+pub fn path_for_url_with(
+    root: impl AsRef<Path>,
+    url: impl AsRef<str>,
+    mapping: &PageMapping,
+) -> Result<PathBuf> {
+    let url = url.as_ref();
+    let normalized = NormalizedUrl::parse(url, &mapping.segment_policy)
+        .map_err(|problem| Error::invalid_url(url, problem))?;
+    let candidates = AbsPagePath::candidates(root.as_ref(), &normalized, mapping);
+    candidates
+        .iter()
+        .find(|path| path.as_ref().exists())
+        .or_else(|| candidates.first())
+        .map(|path| path.as_ref().to_path_buf())
+        .ok_or_else(|| Error::invalid_url(url, UrlProblem::Unmapped))
+}
+
+impl AsRef<str> for PageUrl {
+    /// Returns the URL as a string slice.
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::borrow::Borrow<str> for PageUrl {
+    /// Borrows the URL as a string slice, for map lookups.
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for PageUrl {
+    /// Writes the URL.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PageUrl {
+    type Err = Error;
+
+    /// Validates a page URL, see [`PageUrl::parse`].
+    fn from_str(url: &str) -> Result<Self> {
+        Self::parse(url)
+    }
+}
+
+impl TryFrom<&str> for PageUrl {
+    type Error = Error;
+
+    /// Validates a page URL, see [`PageUrl::parse`].
+    fn try_from(url: &str) -> Result<Self> {
+        Self::parse(url)
+    }
+}
+
+impl Serialize for PageUrl {
+    /// Serializes the URL as a string.
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PageUrl {
+    /// Deserializes and validates a URL string.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let url = String::deserialize(deserializer)?;
+        Self::parse(&url).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{TestDir, write_page};
+
+    fn url(url: &str) -> PageUrl {
+        PageUrl::parse(url).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(url("/guides/install").to_string(), "/guides/install");
+        assert_eq!("/guides/".parse::<PageUrl>().unwrap(), url("/guides/"));
+        for invalid in ["", "guides", "//guides", "/guides//install", "/../secret"] {
+            assert!(
+                matches!(PageUrl::parse(invalid), Err(Error::InvalidUrl { url, .. }) if url == invalid),
+                "{invalid:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_request_uri() {
+        let from_uri = |uri| PageUrl::from_request_uri(uri);
+        assert_eq!(
+            from_uri("/guides/install?ref=nav#setup").unwrap(),
+            url("/guides/install")
+        );
+        assert_eq!(from_uri("/guides/#top").unwrap(), url("/guides/"));
+        assert_eq!(from_uri("/v1%2E2").unwrap(), url("/v1.2"));
+        assert_eq!(from_uri("/?q=1").unwrap(), url("/"));
+
+        let problem = |uri| match from_uri(uri) {
+            Err(Error::InvalidUrl { problem, .. }) => problem,
+            result => panic!("{uri}: {result:?}"),
+        };
+        assert_eq!(
+            problem("/guides%2Finstall"),
+            UrlProblem::InvalidSegment("guides/install".into())
+        );
+        assert_eq!(problem("/%zz"), UrlProblem::InvalidPercentEncoding);
+        assert_eq!(problem("/%4"), UrlProblem::InvalidPercentEncoding);
+        assert_eq!(problem("/%ff"), UrlProblem::InvalidPercentEncoding);
+        assert_eq!(
+            problem("/%2E%2E/secret"),
+            UrlProblem::InvalidSegment("..".into())
+        );
+        assert_eq!(
+            problem("/my%20page"),
+            UrlProblem::InvalidSegment("my page".into())
+        );
+        assert_eq!(problem("?q=1"), UrlProblem::Empty);
+    }
+
+    #[test]
+    fn test_segment_policy() {
+        let unicode = SegmentPolicy::Unicode;
+        assert!(PageUrl::parse("/über-uns").is_err());
+        let page = PageUrl::parse_with("/u\u{308}ber-uns", &unicode).unwrap();
+        assert_eq!(page.as_str(), "/über-uns");
+        assert_eq!(
+            PageUrl::from_request_uri_with("/%D0%BE-%D0%BD%D0%B0%D1%81?x", &unicode)
+                .unwrap()
+                .as_str(),
+            "/о-нас"
+        );
+        let folder = PageUrl::parse_with("/über/", &unicode).unwrap();
+        assert_eq!(folder.parent().unwrap(), url("/"));
+        assert_eq!(
+            folder.join_with("kontakt", &unicode).unwrap().as_str(),
+            "/über/kontakt"
+        );
+        assert!(folder.join("kontakt").is_err());
+    }
+
+    #[test]
+    fn test_segments() {
+        assert_eq!(url("/").segments().count(), 0);
+        assert_eq!(
+            url("/guides/install").segments().collect::<Vec<_>>(),
+            vec!["guides", "install"]
+        );
+        assert_eq!(
+            url("/guides/").segments().collect::<Vec<_>>(),
+            vec!["guides"]
+        );
+        assert!(url("/").is_index());
+        assert!(url("/guides/").is_index());
+        assert!(!url("/guides").is_index());
+    }
+
+    #[test]
+    fn test_parent_and_join() {
+        assert_eq!(url("/").parent(), None);
+        assert_eq!(url("/guides").parent(), Some(url("/")));
+        assert_eq!(url("/guides/").parent(), Some(url("/")));
+        assert_eq!(url("/guides/install").parent(), Some(url("/guides/")));
+        assert_eq!(url("/guides/advanced/").parent(), Some(url("/guides/")));
+
+        assert_eq!(url("/").join("guides/").unwrap(), url("/guides/"));
+        assert_eq!(
+            url("/guides/").join("install").unwrap(),
+            url("/guides/install")
+        );
+        assert_eq!(
+            url("/guides/setup").join("install").unwrap(),
+            url("/guides/install")
+        );
+        assert!(url("/guides/").join("../secret").is_err());
+        assert!(url("/guides/").join("./install").is_err());
+        assert!(url("/guides/").join("/about").is_err());
+    }
+
+    #[test]
+    fn test_to_rel_path() {
+        assert_eq!(url("/").to_rel_path(), Path::new("index.md"));
+        assert_eq!(url("/guides/").to_rel_path(), Path::new("guides/index.md"));
+        assert_eq!(
+            url("/guides/install").to_rel_path(),
+            Path::new("guides/install.md")
+        );
+    }
+
+    #[test]
+    fn test_url_for_path() {
+        assert_eq!(url_for_path("/site", "index.md").unwrap(), url("/"));
+        assert_eq!(
+            url_for_path("/site", "/site/guides/install.md").unwrap(),
+            url("/guides/install")
+        );
+        // Relative paths are always relative to the root.
+        assert_eq!(
+            url_for_path("docs", "docs/intro.md").unwrap(),
+            url("/docs/intro")
+        );
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(
+            url_for_path("pages", cwd.join("pages/guides/index.md")).unwrap(),
+            url("/guides/")
+        );
+        assert_eq!(
+            url_for_path("./pages", cwd.join("pages/guides/index.md")).unwrap(),
+            url("/guides/")
+        );
+
+        let mapping = PageMapping::new()
+            .segment_policy(SegmentPolicy::Unicode)
+            .index_names(["index", "README"])
+            .extensions(["md", "markdown"]);
+        assert_eq!(
+            url_for_path_with("/site", "о-нас/README.markdown", &mapping).unwrap(),
+            PageUrl::parse_with("/о-нас/", &SegmentPolicy::Unicode).unwrap()
+        );
+
+        let problem = |path| match url_for_path("/site", path) {
+            Err(Error::InvalidPath { problem, .. }) => problem,
+            result => panic!("{path}: {result:?}"),
+        };
+        assert_eq!(problem("/elsewhere/index.md"), PathProblem::OutsideRoot);
+        assert_eq!(problem("../index.md"), PathProblem::NotNormalized);
+        assert_eq!(problem("guides/logo.png"), PathProblem::NotMarkdown);
+        assert_eq!(
+            problem("my guides/index.md"),
+            PathProblem::InvalidSegment("my guides".into())
+        );
+    }
+
+    #[test]
+    fn test_path_for_url() {
+        assert_eq!(
+            path_for_url("/site", "/").unwrap(),
+            Path::new("/site/index.md")
+        );
+        assert_eq!(
+            path_for_url("/site", "/guides/").unwrap(),
+            Path::new("/site/guides/index.md")
+        );
+        let root = TestDir::new();
+        let mapping = PageMapping::new()
+            .index_names(["index", "README"])
+            .extensions(["md", "markdown"]);
+        assert_eq!(
+            path_for_url_with(root.path(), "/guides/", &mapping).unwrap(),
+            root.path().join("guides/index.md")
+        );
+        write_page(root.path(), "guides/README.md", "# Guides");
+        assert_eq!(
+            path_for_url_with(root.path(), "/guides/", &mapping).unwrap(),
+            root.path().join("guides/README.md")
+        );
+        for url in ["/guides/README", "/guides/index"] {
+            assert!(matches!(
+                path_for_url_with(root.path(), url, &mapping),
+                Err(Error::InvalidUrl {
+                    problem: UrlProblem::Unmapped,
+                    ..
+                })
+            ));
+        }
+        assert!(matches!(
+            path_for_url("/site", "/guides//install"),
+            Err(Error::InvalidUrl {
+                problem: UrlProblem::EmptySegment,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_serde() {
+        assert_eq!(
+            serde_json::to_string(&url("/guides/")).unwrap(),
+            "\"/guides/\""
+        );
+        assert_eq!(
+            serde_json::from_str::<PageUrl>("\"/guides/install\"").unwrap(),
+            url("/guides/install")
+        );
+        assert!(serde_json::from_str::<PageUrl>("\"guides\"").is_err());
+    }
+}
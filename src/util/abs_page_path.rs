@@ -3,22 +3,23 @@
 use std::path::{Path, PathBuf};
 
 use super::{NormalizedUrl, RelPagePath};
+use crate::PageMapping;
 
 /// Absolute Markdown path
 pub(crate) struct AbsPagePath(PathBuf);
 
 impl AbsPagePath {
-    /// Converts a URL into an absolute Markdown path under the given root.
-    pub(crate) fn from_raw_url(root: &Path, url: &str) -> Option<Self> {
-        let url = NormalizedUrl::try_from(url).ok()?;
-        Some(Self::from_normalized_url(root, &url))
-    }
-
-    /// Converts a normalized URL into an absolute Markdown path under the given
-    /// root.
-    pub(crate) fn from_normalized_url(root: &Path, url: &NormalizedUrl<'_>) -> Self {
-        let rel = RelPagePath::from(url);
-        Self(root.join(rel.as_ref()))
+    /// Returns the absolute paths a normalized URL may be stored at under the
+    /// given root, most preferred first.
+    pub(crate) fn candidates(
+        root: &Path,
+        url: &NormalizedUrl<'_>,
+        mapping: &PageMapping,
+    ) -> Vec<Self> {
+        RelPagePath::candidates(url, mapping)
+            .into_iter()
+            .map(|rel| Self(root.join(rel.as_ref())))
+            .collect()
     }
 }
 
@@ -0,0 +1,322 @@
+//! Minimal UTC timestamp parsing and formatting.
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+
+/// Abbreviated weekday names starting from Monday.
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+/// Abbreviated month names.
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+/// Seconds in a day.
+const DAY: i64 = 86_400;
+
+/// Seconds since the Unix epoch, in UTC.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub(crate) struct Timestamp(i64);
+
+impl Timestamp {
+    /// 1970-01-01T00:00:00Z
+    pub(crate) const EPOCH: Self = Self(0);
+
+    /// Parses `YYYY-MM-DD`, optionally followed by `THH:MM[:SS[.fraction]]`
+    /// and a `Z` or `±HH:MM` offset, as used in frontmatter and RFC 3339.
+    ///
+    /// Times without an offset are treated as UTC.
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (date, time) = match text.find(['T', 't', ' ']) {
+            Some(i) => (&text[..i], Some(&text[i + 1..])),
+            None => (text, None),
+        };
+        let mut parts = date.split('-');
+        let year = parse_number(parts.next()?, 4)?;
+        let month = parse_number(parts.next()?, 2)?;
+        let day = parse_number(parts.next()?, 2)?;
+        if parts.next().is_some() || !(1..=12).contains(&month) {
+            return None;
+        }
+        if day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        let mut seconds = days_from_civil(year, month, day) * DAY;
+
+        if let Some(time) = time {
+            let (time, offset) = split_offset(time)?;
+            let mut parts = time.split(':');
+            let hour = parse_number(parts.next()?, 2)?;
+            let minute = parse_number(parts.next()?, 2)?;
+            let second = match parts.next() {
+                Some(second) => parse_number(second.split('.').next()?, 2)?,
+                None => 0,
+            };
+            if parts.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+                return None;
+            }
+            seconds += hour * 3600 + minute * 60 + second - offset;
+        }
+        Some(Self(seconds))
+    }
+
+    /// Parses an HTTP date such as `Sun, 06 Nov 1994 08:49:37 GMT`.
+    pub(crate) fn parse_http_date(text: &str) -> Option<Self> {
+        let (_, text) = text.trim().split_once(", ")?;
+        let mut parts = text.split(' ');
+        let day = parse_number(parts.next()?, 2)?;
+        let month = parts.next()?;
+        let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+        let year = parse_number(parts.next()?, 4)?;
+        let time = parts.next()?;
+        if parts.next()? != "GMT" || parts.next().is_some() {
+            return None;
+        }
+        Self::parse(&format!("{year:04}-{month:02}-{day:02}T{time}Z"))
+    }
+
+    /// Returns the date as `YYYY-MM-DD`.
+    pub(crate) fn to_date(self) -> String {
+        let (year, month, day) = civil_from_days(self.0.div_euclid(DAY));
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+
+    /// Returns the timestamp in RFC 3339 format, such as
+    /// `1994-11-06T08:49:37Z`.
+    pub(crate) fn to_rfc3339(self) -> String {
+        let (hour, minute, second) = self.time();
+        format!("{}T{hour:02}:{minute:02}:{second:02}Z", self.to_date())
+    }
+
+    /// Returns the timestamp in RFC 2822 format, such as
+    /// `Sun, 06 Nov 1994 08:49:37 +0000`.
+    pub(crate) fn to_rfc2822(self) -> String {
+        format!("{} +0000", self.format_with_weekday())
+    }
+
+    /// Returns the timestamp as an HTTP date, such as
+    /// `Sun, 06 Nov 1994 08:49:37 GMT`.
+    pub(crate) fn to_http_date(self) -> String {
+        format!("{} GMT", self.format_with_weekday())
+    }
+
+    /// Formats the shared part of RFC 2822 and HTTP dates.
+    fn format_with_weekday(self) -> String {
+        let days = self.0.div_euclid(DAY);
+        let (year, month, day) = civil_from_days(days);
+        let (hour, minute, second) = self.time();
+        // 1970-01-01 was a Thursday.
+        let weekday = WEEKDAYS[(days + 3).rem_euclid(7) as usize];
+        let month = MONTHS[month as usize - 1];
+        format!("{weekday}, {day:02} {month} {year:04} {hour:02}:{minute:02}:{second:02}")
+    }
+
+    /// Returns hours, minutes and seconds of the day.
+    fn time(self) -> (i64, i64, i64) {
+        let seconds = self.0.rem_euclid(DAY);
+        (seconds / 3600, seconds % 3600 / 60, seconds % 60)
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    /// Truncates a system time to whole seconds.
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => Self(since.as_secs() as i64),
+            Err(error) => Self(-(error.duration().as_secs_f64().ceil() as i64)),
+        }
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    /// Converts a timestamp back into a system time.
+    fn from(timestamp: Timestamp) -> Self {
+        let seconds = Duration::from_secs(timestamp.0.unsigned_abs());
+        if timestamp.0 >= 0 {
+            UNIX_EPOCH + seconds
+        } else {
+            UNIX_EPOCH - seconds
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    /// Deserializes a date string, or a TOML date or datetime.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// Accepts strings and TOML's internal datetime representation.
+        struct TimestampVisitor;
+
+        impl<'de> Visitor<'de> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a date such as 2024-01-31 or 2024-01-31T10:00:00Z")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Timestamp, E> {
+                Timestamp::parse(value)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Timestamp, A::Error> {
+                let Some((_, value)) = map.next_entry::<String, String>()? else {
+                    return Err(de::Error::invalid_length(0, &self));
+                };
+                self.visit_str(&value)
+            }
+        }
+
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+}
+
+/// Parses a fixed-width unsigned decimal number.
+fn parse_number(text: &str, width: usize) -> Option<i64> {
+    if text.len() != width || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Splits a time into the time itself and the UTC offset in seconds.
+fn split_offset(time: &str) -> Option<(&str, i64)> {
+    if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        return Some((time, 0));
+    }
+    let Some(i) = time.rfind(['+', '-']) else {
+        return Some((time, 0));
+    };
+    let (hours, minutes) = time[i + 1..].split_once(':')?;
+    let offset = parse_number(hours, 2)? * 3600 + parse_number(minutes, 2)? * 60;
+    let sign = if time[i..].starts_with('-') { -1 } else { 1 };
+    Some((&time[..i], sign * offset))
+}
+
+/// Returns the number of days in a month.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days since 1970-01-01 for a civil date.
+///
+/// Based on Howard Hinnant's `days_from_civil` algorithm.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the civil date for a number of days since 1970-01-01.
+///
+/// Based on Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::Timestamp;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Timestamp::parse("1970-01-01"), Some(Timestamp(0)));
+        assert_eq!(
+            Timestamp::parse("1994-11-06T08:49:37Z"),
+            Some(Timestamp(784_111_777))
+        );
+        assert_eq!(
+            Timestamp::parse("1994-11-06T10:49:37.123+02:00"),
+            Some(Timestamp(784_111_777))
+        );
+        assert_eq!(
+            Timestamp::parse("1994-11-06 08:49"),
+            Some(Timestamp(784_111_740))
+        );
+        assert_eq!(
+            Timestamp::parse("2024-02-29").unwrap().to_date(),
+            "2024-02-29"
+        );
+
+        for invalid in [
+            "",
+            "2024",
+            "2024-1-01",
+            "2023-02-29",
+            "2024-13-01",
+            "2024-01-01T25:00",
+        ] {
+            assert_eq!(Timestamp::parse(invalid), None, "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_format() {
+        let timestamp = Timestamp(784_111_777);
+        assert_eq!(timestamp.to_date(), "1994-11-06");
+        assert_eq!(timestamp.to_rfc3339(), "1994-11-06T08:49:37Z");
+        assert_eq!(timestamp.to_rfc2822(), "Sun, 06 Nov 1994 08:49:37 +0000");
+        assert_eq!(timestamp.to_http_date(), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(Timestamp(-1).to_rfc3339(), "1969-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_http_date() {
+        assert_eq!(
+            Timestamp::parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(Timestamp(784_111_777))
+        );
+        assert_eq!(
+            Timestamp::parse_http_date("Sun, 06 Nov 1994 08:49:37"),
+            None
+        );
+        assert_eq!(Timestamp::parse_http_date("1994-11-06"), None);
+    }
+
+    #[test]
+    fn test_system_time() {
+        let time = UNIX_EPOCH + Duration::from_millis(784_111_777_500);
+        assert_eq!(Timestamp::from(time), Timestamp(784_111_777));
+        assert_eq!(
+            SystemTime::from(Timestamp(784_111_777)),
+            UNIX_EPOCH + Duration::from_secs(784_111_777)
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<Timestamp>("\"1970-01-02\"").unwrap(),
+            Timestamp(86_400)
+        );
+        assert!(serde_json::from_str::<Timestamp>("\"yesterday\"").is_err());
+    }
+}
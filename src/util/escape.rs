@@ -0,0 +1,96 @@
+//! HTML and XML text escaping and URL percent-encoding.
+
+use std::borrow::Cow;
+
+/// Escapes `&`, `<`, `>`, `"` and `'` for use in HTML or XML text and
+/// attribute values.
+pub(crate) fn escape_html(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Decodes `%XX` escapes of a URL segment, or returns `None` for malformed
+/// escapes and non-UTF-8 results.
+pub(crate) fn percent_decode(segment: &str) -> Option<String> {
+    if !segment.contains('%') {
+        return Some(segment.to_string());
+    }
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Percent-encodes the characters of a URL path that aren't allowed in URIs,
+/// such as non-ASCII letters and spaces, keeping the slashes.
+pub(crate) fn percent_encode_path(path: &str) -> Cow<'_, str> {
+    let is_allowed =
+        |byte: u8| byte.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=:@".contains(&byte);
+    if path.bytes().all(is_allowed) {
+        return Cow::Borrowed(path);
+    }
+    let mut encoded = String::with_capacity(path.len() * 3);
+    for byte in path.bytes() {
+        if is_allowed(byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    Cow::Owned(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{escape_html, percent_decode, percent_encode_path};
+
+    #[test]
+    fn test_escape_html() {
+        assert!(matches!(escape_html("foo"), Cow::Borrowed("foo")));
+        assert_eq!(
+            escape_html("<a href=\"x\">Tom & 'Jerry'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_percent_encoding() {
+        assert!(matches!(
+            percent_encode_path("/guides/v1.2"),
+            Cow::Borrowed("/guides/v1.2")
+        ));
+        assert_eq!(percent_encode_path("/über uns/"), "/%C3%BCber%20uns/");
+        assert_eq!(
+            percent_decode("%C3%BCber%20uns").as_deref(),
+            Some("über uns")
+        );
+        assert_eq!(percent_decode("%zz"), None);
+    }
+}
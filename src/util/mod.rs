@@ -1,12 +1,19 @@
-//! Internal typed helpers for page URLs and paths.
+//! Internal typed helpers for page URLs and paths, dates, escaping and URL
+//! schemes.
 
 pub(crate) mod abs_page_path;
+pub(crate) mod date;
+pub(crate) mod escape;
 pub(crate) mod normalized_url;
 mod page_location;
 pub(crate) mod page_segment;
 pub(crate) mod rel_page_path;
+pub(crate) mod scheme;
 
 pub(crate) use abs_page_path::AbsPagePath;
+pub(crate) use date::Timestamp;
+pub(crate) use escape::{escape_html, percent_decode, percent_encode_path};
 pub(crate) use normalized_url::NormalizedUrl;
 pub(crate) use page_segment::is_valid_page_segment;
 pub(crate) use rel_page_path::RelPagePath;
+pub(crate) use scheme::{is_external_url, url_scheme};
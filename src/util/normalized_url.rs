@@ -1,49 +1,102 @@
 //! Normalized page URL parsing and conversions.
 
-use std::borrow::{Borrow, Cow};
+use std::{
+    borrow::{Borrow, Cow},
+    path::Path,
+};
 
-use super::{RelPagePath, is_valid_page_segment, page_location::PageLocation};
+use super::{RelPagePath, page_location::PageLocation};
+use crate::{
+    PageMapping, SegmentPolicy,
+    error::{PathProblem, UrlProblem},
+};
 
 /// Canonical page URL.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub(crate) struct NormalizedUrl<'a>(Cow<'a, str>);
 
 impl<'a> TryFrom<&'a str> for NormalizedUrl<'a> {
-    type Error = ();
+    type Error = UrlProblem;
 
     /// Validates and wraps a raw page URL string.
     fn try_from(url: &'a str) -> Result<Self, Self::Error> {
-        let original_url = url;
-        if url.is_empty() {
-            return Err(());
-        }
+        Self::parse(url, &SegmentPolicy::Ascii)
+    }
+}
 
-        if url == "/" {
-            return Ok(Self(Cow::Borrowed("/")));
-        }
+impl<'a> NormalizedUrl<'a> {
+    /// Normalizes and validates a raw page URL string with a segment policy.
+    ///
+    /// The input is borrowed unless the policy normalizes it.
+    pub(crate) fn parse(url: &'a str, policy: &SegmentPolicy) -> Result<Self, UrlProblem> {
+        let url = policy.normalize(url);
+        validate(&url, policy)?;
+        Ok(Self(url))
+    }
 
-        if !url.starts_with('/') {
-            return Err(());
-        }
+    /// Returns the folder index containing the page, such as `/guides/` for
+    /// both `/guides/install` and `/guides/advanced/`, or `None` for `/`.
+    pub(crate) fn parent(&self) -> Option<NormalizedUrl<'_>> {
+        let url = self.0.as_ref();
+        let trimmed = url.strip_suffix('/').unwrap_or(url);
+        let end = trimmed.rfind('/')?;
+        Some(NormalizedUrl(Cow::Borrowed(&url[..=end])))
+    }
+}
 
-        let url = url.strip_prefix('/').unwrap_or(url);
-        let url = if url.ends_with('/') {
-            url.strip_suffix('/').unwrap_or(url)
-        } else {
-            url
-        };
+impl NormalizedUrl<'static> {
+    /// Converts a relative Markdown path into a normalized URL with a page
+    /// mapping.
+    pub(crate) fn from_path(path: &Path, mapping: &PageMapping) -> Result<Self, PathProblem> {
+        let url = Self::from(PageLocation::parse(path, mapping)?);
+        Ok(match mapping.segment_policy.normalize(url.as_ref()) {
+            Cow::Owned(normalized) => Self(Cow::Owned(normalized)),
+            Cow::Borrowed(_) => url,
+        })
+    }
+}
 
-        if url.is_empty() {
-            return Err(());
-        }
+/// Checks the structure of a URL and its segments.
+fn validate(url: &str, policy: &SegmentPolicy) -> Result<(), UrlProblem> {
+    if url.is_empty() {
+        return Err(UrlProblem::Empty);
+    }
 
-        for segment in url.split('/') {
-            if !is_valid_page_segment(segment) {
-                return Err(());
-            }
+    if url == "/" {
+        return Ok(());
+    }
+
+    if !url.starts_with('/') {
+        return Err(UrlProblem::MissingLeadingSlash);
+    }
+
+    let url = url.strip_prefix('/').unwrap_or(url);
+    let url = if url.ends_with('/') {
+        url.strip_suffix('/').unwrap_or(url)
+    } else {
+        url
+    };
+
+    if url.is_empty() {
+        return Err(UrlProblem::EmptySegment);
+    }
+
+    for segment in url.split('/') {
+        if segment.is_empty() {
+            return Err(UrlProblem::EmptySegment);
+        }
+        if !policy.is_valid(segment) {
+            return Err(UrlProblem::InvalidSegment(segment.to_string()));
         }
+    }
+
+    Ok(())
+}
 
-        Ok(Self(Cow::Borrowed(original_url)))
+impl NormalizedUrl<'_> {
+    /// Converts the URL into an owned one.
+    pub(crate) fn into_owned(self) -> NormalizedUrl<'static> {
+        NormalizedUrl(Cow::Owned(self.0.into_owned()))
     }
 }
 
@@ -79,7 +132,7 @@ impl From<PageLocation<'_>> for NormalizedUrl<'static> {
 }
 
 impl TryFrom<&RelPagePath> for NormalizedUrl<'static> {
-    type Error = ();
+    type Error = PathProblem;
 
     /// Converts a validated relative page path into a normalized URL.
     fn try_from(path: &RelPagePath) -> Result<Self, Self::Error> {
@@ -92,19 +145,73 @@ mod tests {
     use std::{borrow::Cow, path::Path};
 
     use super::NormalizedUrl;
-    use crate::util::RelPagePath;
+    use crate::{PageMapping, SegmentPolicy, error::UrlProblem, util::RelPagePath};
 
     #[test]
     fn test_try_from_rejects_empty_segments() {
-        assert!(NormalizedUrl::try_from("foo").is_err());
-        assert!(NormalizedUrl::try_from("//foo").is_err());
+        assert_eq!(
+            NormalizedUrl::try_from("foo"),
+            Err(UrlProblem::MissingLeadingSlash)
+        );
+        assert_eq!(
+            NormalizedUrl::try_from("//foo"),
+            Err(UrlProblem::EmptySegment)
+        );
         assert!(NormalizedUrl::try_from("foo//").is_err());
-        assert!(NormalizedUrl::try_from("foo//bar").is_err());
-        assert!(NormalizedUrl::try_from("////").is_err());
+        assert_eq!(
+            NormalizedUrl::try_from("/foo//bar"),
+            Err(UrlProblem::EmptySegment)
+        );
+        assert_eq!(
+            NormalizedUrl::try_from("////"),
+            Err(UrlProblem::EmptySegment)
+        );
+        assert_eq!(NormalizedUrl::try_from(""), Err(UrlProblem::Empty));
+        assert_eq!(
+            NormalizedUrl::try_from("/foo/../bar"),
+            Err(UrlProblem::InvalidSegment("..".into()))
+        );
         assert_eq!(NormalizedUrl::try_from("/foo/").unwrap().as_ref(), "/foo/");
         assert_eq!(NormalizedUrl::try_from("/foo").unwrap().as_ref(), "/foo");
     }
 
+    #[test]
+    fn test_parse_with_policy() {
+        let unicode = SegmentPolicy::Unicode;
+        assert_eq!(
+            NormalizedUrl::parse("/о-нас/", &unicode).unwrap().as_ref(),
+            "/о-нас/"
+        );
+        let normalized = NormalizedUrl::parse("/u\u{308}ber-uns", &unicode).unwrap();
+        assert_eq!(normalized.as_ref(), "/über-uns");
+        assert_eq!(
+            NormalizedUrl::try_from("/über-uns"),
+            Err(UrlProblem::InvalidSegment("über-uns".into()))
+        );
+        assert_eq!(
+            NormalizedUrl::from_path(
+                Path::new("u\u{308}ber/index.md"),
+                &PageMapping::new().segment_policy(unicode)
+            )
+            .unwrap()
+            .as_ref(),
+            "/über/"
+        );
+    }
+
+    #[test]
+    fn test_parent() {
+        let parent = |url| {
+            NormalizedUrl::try_from(url)
+                .unwrap()
+                .parent()
+                .map(|parent| parent.as_ref().to_string())
+        };
+        assert_eq!(parent("/"), None);
+        assert_eq!(parent("/install").as_deref(), Some("/"));
+        assert_eq!(parent("/guides/advanced/").as_deref(), Some("/guides/"));
+    }
+
     #[test]
     fn test_try_from_borrows_normalized_input() {
         let root = NormalizedUrl::try_from("/").unwrap();
@@ -2,7 +2,8 @@
 
 use std::path::{Component, Path};
 
-use super::{NormalizedUrl, RelPagePath, is_valid_page_segment};
+use super::{NormalizedUrl, RelPagePath};
+use crate::{PageMapping, error::PathProblem};
 
 /// Shared page-location representation used by URL and path conversions.
 #[derive(Debug, Eq, PartialEq)]
@@ -46,26 +47,40 @@ impl<'a> From<&'a NormalizedUrl<'_>> for PageLocation<'a> {
 }
 
 impl<'a> TryFrom<&'a Path> for PageLocation<'a> {
-    type Error = ();
+    type Error = PathProblem;
 
     /// Parses a relative Markdown path into its logical page location.
     fn try_from(path: &'a Path) -> Result<Self, Self::Error> {
+        Self::parse(path, &PageMapping::default())
+    }
+}
+
+impl<'a> PageLocation<'a> {
+    /// Parses a relative Markdown path, recognizing the index names and
+    /// extensions of a mapping and checking the normalized segments against
+    /// its policy.
+    pub(super) fn parse(path: &'a Path, mapping: &PageMapping) -> Result<Self, PathProblem> {
+        let policy = &mapping.segment_policy;
+        let is_valid = |segment: &str| policy.is_valid(&policy.normalize(segment));
         let mut components = Vec::new();
         for component in path.components() {
             let Component::Normal(segment) = component else {
-                return Err(());
+                return Err(PathProblem::NotNormalized);
             };
-            components.push(segment.to_str().ok_or(())?);
+            components.push(segment.to_str().ok_or(PathProblem::NotUtf8)?);
         }
 
-        let file_name = components.pop().ok_or(())?;
+        let file_name = components.pop().ok_or(PathProblem::Empty)?;
         for segment in &components {
-            if !is_valid_page_segment(segment) {
-                return Err(());
+            if !is_valid(segment) {
+                return Err(PathProblem::InvalidSegment(segment.to_string()));
             }
         }
 
-        if file_name == "index.md" {
+        let (stem, _) = mapping
+            .split_extension(file_name)
+            .ok_or(PathProblem::NotMarkdown)?;
+        if mapping.index_rank(stem).is_some() {
             return if components.is_empty() {
                 Ok(Self::Root)
             } else {
@@ -73,9 +88,8 @@ impl<'a> TryFrom<&'a Path> for PageLocation<'a> {
             };
         }
 
-        let stem = file_name.strip_suffix(".md").ok_or(())?;
-        if !is_valid_page_segment(stem) {
-            return Err(());
+        if !is_valid(stem) {
+            return Err(PathProblem::InvalidSegment(file_name.to_string()));
         }
         Ok(Self::File {
             path: components,
@@ -85,7 +99,7 @@ impl<'a> TryFrom<&'a Path> for PageLocation<'a> {
 }
 
 impl<'a> TryFrom<&'a RelPagePath> for PageLocation<'a> {
-    type Error = ();
+    type Error = PathProblem;
 
     /// Parses the wrapped relative page path into its logical page location.
     fn try_from(path: &'a RelPagePath) -> Result<Self, Self::Error> {
@@ -98,7 +112,7 @@ mod tests {
     use std::path::Path;
 
     use super::PageLocation;
-    use crate::util::NormalizedUrl;
+    use crate::{error::PathProblem, util::NormalizedUrl};
 
     #[test]
     fn test_from_normalized_url() {
@@ -148,8 +162,29 @@ mod tests {
             }
         );
 
-        assert!(PageLocation::try_from(Path::new("../secret.md")).is_err());
-        assert!(PageLocation::try_from(Path::new("guides/../secret.md")).is_err());
-        assert!(PageLocation::try_from(Path::new("guides/readme.txt")).is_err());
+        assert_eq!(
+            PageLocation::try_from(Path::new("../secret.md")),
+            Err(PathProblem::NotNormalized)
+        );
+        assert_eq!(
+            PageLocation::try_from(Path::new("guides/../secret.md")),
+            Err(PathProblem::NotNormalized)
+        );
+        assert_eq!(
+            PageLocation::try_from(Path::new("guides/readme.txt")),
+            Err(PathProblem::NotMarkdown)
+        );
+        assert_eq!(
+            PageLocation::try_from(Path::new("my guides/install.md")),
+            Err(PathProblem::InvalidSegment("my guides".into()))
+        );
+        assert_eq!(
+            PageLocation::try_from(Path::new("guides/in?stall.md")),
+            Err(PathProblem::InvalidSegment("in?stall.md".into()))
+        );
+        assert_eq!(
+            PageLocation::try_from(Path::new("")),
+            Err(PathProblem::Empty)
+        );
     }
 }
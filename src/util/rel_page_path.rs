@@ -3,6 +3,7 @@
 use std::path::{Path, PathBuf};
 
 use super::{NormalizedUrl, page_location::PageLocation};
+use crate::{PageMapping, error::PathProblem};
 
 /// Relative Markdown path
 pub(crate) struct RelPagePath(PathBuf);
@@ -15,7 +16,7 @@ impl From<&NormalizedUrl<'_>> for RelPagePath {
 }
 
 impl TryFrom<&Path> for RelPagePath {
-    type Error = ();
+    type Error = PathProblem;
 
     /// Validates and wraps a relative Markdown path.
     fn try_from(path: &Path) -> Result<Self, Self::Error> {
@@ -24,6 +25,26 @@ impl TryFrom<&Path> for RelPagePath {
     }
 }
 
+impl RelPagePath {
+    /// Returns the relative paths a normalized URL may be stored at with a
+    /// page mapping, most preferred first.
+    ///
+    /// URLs ending with an index name, such as `/guides/index`, have none, as
+    /// index files are pages of their folder URL.
+    pub(crate) fn candidates(url: &NormalizedUrl<'_>, mapping: &PageMapping) -> Vec<Self> {
+        let (segments, names): (Vec<&str>, Vec<String>) = match PageLocation::from(url) {
+            PageLocation::Root => (Vec::new(), mapping.index_files().collect()),
+            PageLocation::Index(segments) => (segments, mapping.index_files().collect()),
+            PageLocation::File { name, .. } if mapping.index_rank(name).is_some() => {
+                return Vec::new();
+            }
+            PageLocation::File { path, name } => (path, mapping.page_files(name).collect()),
+        };
+        let dir = segments.iter().collect::<PathBuf>();
+        names.into_iter().map(|name| Self(dir.join(name))).collect()
+    }
+}
+
 impl AsRef<Path> for RelPagePath {
     /// Returns the wrapped relative path.
     fn as_ref(&self) -> &Path {
@@ -64,7 +85,7 @@ mod tests {
     use std::path::Path;
 
     use super::RelPagePath;
-    use crate::util::NormalizedUrl;
+    use crate::{PageMapping, util::NormalizedUrl};
 
     #[test]
     fn test_from_normalized_url() {
@@ -86,4 +107,38 @@ mod tests {
             Path::new("guides/index.md")
         );
     }
+
+    #[test]
+    fn test_candidates() {
+        let mapping = PageMapping::new()
+            .index_names(["index", "README"])
+            .extensions(["md", "markdown"]);
+        let candidates = |url| {
+            RelPagePath::candidates(&NormalizedUrl::try_from(url).unwrap(), &mapping)
+                .into_iter()
+                .map(|path| path.0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            candidates("/guides/"),
+            [
+                "guides/index.md",
+                "guides/index.markdown",
+                "guides/README.md",
+                "guides/README.markdown"
+            ]
+            .map(Path::new)
+        );
+        assert_eq!(
+            candidates("/guides/install"),
+            ["guides/install.md", "guides/install.markdown"].map(Path::new)
+        );
+        assert_eq!(candidates("/")[2], Path::new("README.md"));
+        assert!(candidates("/guides/README").is_empty());
+        assert!(candidates("/index").is_empty());
+        assert_eq!(
+            candidates("/readme"),
+            ["readme.md", "readme.markdown"].map(Path::new)
+        );
+    }
 }
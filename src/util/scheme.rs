@@ -0,0 +1,62 @@
+//! URL scheme detection shared by rendering, link checks and redirects.
+
+/// Returns the lowercased scheme of a URL, such as `https` or `mailto`.
+///
+/// Like browsers, leading spaces and control characters are ignored, as are
+/// tabs and newlines inside the scheme, so `java\tscript:` is detected too.
+pub(crate) fn url_scheme(url: &str) -> Option<String> {
+    let (scheme, _) = trim_url_start(url).split_once(':')?;
+    let scheme = scheme
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>();
+    let mut chars = scheme.chars();
+    let is_valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+    is_valid.then(|| scheme.to_ascii_lowercase())
+}
+
+/// Returns whether a URL points outside the site, having a scheme such as
+/// `https:` or a host such as `//example.com`.
+pub(crate) fn is_external_url(url: &str) -> bool {
+    trim_url_start(url).starts_with("//") || url_scheme(url).is_some()
+}
+
+/// Strips the leading spaces and control characters browsers ignore.
+fn trim_url_start(url: &str) -> &str {
+    url.trim_start_matches(|c: char| c <= ' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_external_url, url_scheme};
+
+    #[test]
+    fn test_url_scheme() {
+        assert_eq!(url_scheme("https://x.org").as_deref(), Some("https"));
+        assert_eq!(url_scheme("Mailto:a@b.c").as_deref(), Some("mailto"));
+        assert_eq!(url_scheme(" java\tscript:x").as_deref(), Some("javascript"));
+        assert_eq!(url_scheme("svn+ssh://x").as_deref(), Some("svn+ssh"));
+        for url in [
+            "/foo:bar",
+            "foo/bar:baz",
+            "1a:b",
+            "#a:b",
+            "?a:b",
+            "install",
+            "",
+        ] {
+            assert_eq!(url_scheme(url), None, "{url:?}");
+        }
+    }
+
+    #[test]
+    fn test_is_external_url() {
+        for url in ["https://x.org", "//x.org/a", " //x.org", "mailto:a@b.c"] {
+            assert!(is_external_url(url), "{url:?}");
+        }
+        for url in ["/a", "a/b", "../a", "#a", "/a:b"] {
+            assert!(!is_external_url(url), "{url:?}");
+        }
+    }
+}